@@ -8,9 +8,9 @@
 //!
 //! This code is adapted from https://github.com/Ogmo-Editor-3/ogmo-3-lib/blob/master/sample/Main.hx.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
-use hashbrown::HashMap;
 use ogmo3::{Layer, Level, Project};
 use tetra::graphics::{self, Color, DrawParams, Rectangle, Texture};
 use tetra::math::Vec2;
@@ -26,10 +26,12 @@ struct GameState {
 impl GameState {
     fn new(ctx: &mut Context) -> anyhow::Result<GameState> {
         // All paths in Ogmo projects and levels are relative to the project folder.
+        // The project remembers where it was loaded from, so the level paths can be
+        // resolved directly.
 
         let base_path = PathBuf::from("./examples/sample_project");
         let project = Project::from_file(base_path.join("test.ogmo"))?;
-        let level = Level::from_file(base_path.join("levels/uno.json"))?;
+        let level = Level::from_file(project.resolve_level_path(0).join("levels/uno.json"))?;
 
         // Most of the project file's data can be ignored at runtime, but it does
         // provide info which can be used to slice up tilesets.
@@ -40,7 +42,7 @@ impl GameState {
         let mut tilesets = Vec::new();
         let mut tileset_mappings = HashMap::new();
 
-        for tileset in project.tilesets {
+        for tileset in &project.tilesets {
             let texture = Texture::new(ctx, base_path.join(&tileset.path))?;
 
             let tiles = tileset
@@ -57,7 +59,7 @@ impl GameState {
 
             let id = tilesets.len();
             tilesets.push(TilesetData { texture, tiles });
-            tileset_mappings.insert(tileset.label, id);
+            tileset_mappings.insert(tileset.label.clone(), id);
         }
 
         // In this example, we convert the layers into a single flat list of sprite data, which we can
@@ -71,8 +73,9 @@ impl GameState {
         let mut sprites = Vec::new();
         let mut decals = Vec::new();
 
-        // TODO: Ogmo allows you to specify layer offsets, which can be useful for creating
-        // chunked levels - this example does not currently take those fields into account.
+        // Ogmo allows you to specify layer offsets, which can be useful for creating
+        // chunked levels. The `unpack_with_offset` methods fold these into the pixel
+        // positions for you - for entities and decals, we add them on manually.
 
         for layer in level.layers {
             match layer {
@@ -80,7 +83,7 @@ impl GameState {
                 // storage options available in the editor. The `unpack` method abstracts over these,
                 // allowing you to quickly pull tile data out of the layer.
                 Layer::Tile(layer) => {
-                    for tile in layer.unpack() {
+                    for tile in layer.unpack_with_offset() {
                         if let Some(id) = tile.id {
                             sprites.push(Sprite::TileIndex {
                                 tileset: tileset_mappings[&layer.tileset],
@@ -96,7 +99,7 @@ impl GameState {
 
                 // An `unpack` method is also available for layers defined using tile co-ordinates.
                 Layer::TileCoords(layer) => {
-                    for tile in layer.unpack() {
+                    for tile in layer.unpack_with_offset() {
                         if let Some(coords) = tile.pixel_coords {
                             sprites.push(Sprite::TileUV {
                                 tileset: tileset_mappings[&layer.tileset],
@@ -117,7 +120,7 @@ impl GameState {
 
                 // An `unpack` method is also available for grid data.
                 Layer::Grid(layer) => {
-                    for cell in layer.unpack() {
+                    for cell in layer.unpack_with_offset() {
                         if cell.value != "0" {
                             sprites.push(Sprite::Rect {
                                 rect: Rectangle::new(
@@ -139,7 +142,12 @@ impl GameState {
                     for entity in &layer.entities {
                         sprites.push(Sprite::Rect {
                             color: Color::RED,
-                            rect: Rectangle::new(entity.x, entity.y, 16.0, 16.0),
+                            rect: Rectangle::new(
+                                entity.x + layer.offset_x,
+                                entity.y + layer.offset_y,
+                                16.0,
+                                16.0,
+                            ),
                         });
                     }
                 }
@@ -150,6 +158,9 @@ impl GameState {
                 // In this example, we load a seperate texture for every decal - in a real game,
                 // you would probably want to make sure you don't load the same texture multiple
                 // times!
+                //
+                // Rotations can be stored in either degrees or radians, depending on the project's
+                // settings, so we convert them to radians before passing them to Tetra.
                 Layer::Decal(layer) => {
                     let folder_path = base_path.join(layer.folder);
 
@@ -160,8 +171,8 @@ impl GameState {
                         decals.push(texture);
                         sprites.push(Sprite::Decal {
                             decal: id,
-                            position: Vec2::new(decal.x, decal.y),
-                            rotation: decal.rotation.unwrap_or(0.0),
+                            position: Vec2::new(decal.x + layer.offset_x, decal.y + layer.offset_y),
+                            rotation: project.to_radians(decal.rotation.unwrap_or(0.0)),
                             scale: Vec2::new(
                                 decal.scale_x.unwrap_or(1.0),
                                 decal.scale_y.unwrap_or(1.0),
@@ -169,6 +180,10 @@ impl GameState {
                         });
                     }
                 }
+
+                // Layers of a type that the crate doesn't recognize (e.g. from a newer version
+                // of Ogmo) are kept as raw JSON - we have no way of drawing them, so skip them.
+                Layer::Unknown(_) => {}
             }
         }
 
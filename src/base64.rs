@@ -0,0 +1,82 @@
+//! A minimal base 64 encoder and decoder, for the images that Ogmo embeds in projects.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::Error;
+
+/// Decodes a standard base 64 string, as used by Ogmo for embedded images.
+///
+/// If the string is a data URL (e.g. `data:image/png;base64,...`), the prefix is
+/// stripped before decoding. Padding is optional.
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>, Error> {
+    let data = match input.find(";base64,") {
+        Some(i) if input.starts_with("data:") => &input[i + ";base64,".len()..],
+        _ => input,
+    };
+
+    let data = data.trim_end_matches('=').as_bytes();
+
+    if data.len() % 4 == 1 {
+        return Err(Error::Base64(format!("invalid length: {}", data.len())));
+    }
+
+    let mut output = Vec::with_capacity(data.len() * 3 / 4);
+
+    for chunk in data.chunks(4) {
+        let mut buffer = 0u32;
+
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = decode_char(c)
+                .ok_or_else(|| Error::Base64(format!("invalid character: '{}'", char::from(c))))?;
+
+            buffer |= u32::from(value) << (18 - 6 * i);
+        }
+
+        let bytes = buffer.to_be_bytes();
+        output.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+
+    Ok(output)
+}
+
+/// Encodes bytes as a PNG data URL (`data:image/png;base64,...`), in the same format
+/// that Ogmo uses for embedded images.
+pub(crate) fn encode_png(input: &[u8]) -> String {
+    const PREFIX: &str = "data:image/png;base64,";
+
+    let mut output = String::with_capacity(PREFIX.len() + input.len().div_ceil(3) * 4);
+    output.push_str(PREFIX);
+
+    for chunk in input.chunks(3) {
+        let mut bytes = [0; 4];
+        bytes[1..=chunk.len()].copy_from_slice(chunk);
+
+        let buffer = u32::from_be_bytes(bytes);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let value = (buffer >> (18 - 6 * i)) & 0x3f;
+                output.push(char::from(ENCODE_TABLE[value as usize]));
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+const ENCODE_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn decode_char(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
@@ -0,0 +1,739 @@
+//! Helpers for writing JSON in the same format as the Ogmo editor.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+use serde::ser::{
+    Error as SerError, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+
+use crate::{PrettyOptions, ValueMap};
+#[cfg(feature = "std")]
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+
+/// The UTF-8 byte order mark, which some editors write at the start of a file.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Removes a leading UTF-8 byte order mark from a slice of JSON, if there is one.
+pub(crate) fn strip_bom(v: &[u8]) -> &[u8] {
+    v.strip_prefix(UTF8_BOM).unwrap_or(v)
+}
+
+/// The largest magnitude at which a float can be safely written as an integer.
+#[cfg(feature = "std")]
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Serializes a value to a JSON string, writing whole-numbered floats without a
+/// decimal point.
+#[cfg(feature = "std")]
+pub(crate) fn to_string<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    write(value, OgmoFormatter(CompactFormatter))
+}
+
+/// Serializes a value to a pretty-printed JSON string, writing whole-numbered floats
+/// without a decimal point.
+#[cfg(feature = "std")]
+pub(crate) fn to_string_pretty<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    write(value, OgmoFormatter(PrettyFormatter::new()))
+}
+
+/// Serializes a value to a JSON string.
+///
+/// serde_json does not support custom formatters without `std`, so whole-numbered
+/// floats will be written with a decimal point.
+#[cfg(not(feature = "std"))]
+pub(crate) fn to_string<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string(value)
+}
+
+/// Serializes a value to a pretty-printed JSON string.
+///
+/// serde_json does not support custom formatters without `std`, so whole-numbered
+/// floats will be written with a decimal point.
+#[cfg(not(feature = "std"))]
+pub(crate) fn to_string_pretty<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(value)
+}
+
+/// Serializes a value to a pretty-printed JSON string, using the given options.
+pub(crate) fn to_string_pretty_with<T: Serialize>(
+    value: &T,
+    options: &PrettyOptions,
+) -> Result<String, serde_json::Error> {
+    if options.sort_keys {
+        let sorted = value.serialize(SortedSerializer)?;
+        pretty_with_indent(&sorted, &options.indent)
+    } else {
+        pretty_with_indent(value, &options.indent)
+    }
+}
+
+#[cfg(feature = "std")]
+fn pretty_with_indent<T: Serialize>(value: &T, indent: &str) -> Result<String, serde_json::Error> {
+    write(
+        value,
+        OgmoFormatter(PrettyFormatter::with_indent(indent.as_bytes())),
+    )
+}
+
+/// serde_json does not support custom formatters without `std`, so this replaces the
+/// default two-space indentation instead. JSON strings can't contain raw newlines, so
+/// every line's leading spaces are indentation.
+#[cfg(not(feature = "std"))]
+fn pretty_with_indent<T: Serialize>(value: &T, indent: &str) -> Result<String, serde_json::Error> {
+    let json = serde_json::to_string_pretty(value)?;
+    let mut output = String::with_capacity(json.len());
+
+    for (i, line) in json.split('\n').enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+
+        let content = line.trim_start_matches(' ');
+
+        for _ in 0..(line.len() - content.len()) / 2 {
+            output.push_str(indent);
+        }
+
+        output.push_str(content);
+    }
+
+    Ok(output)
+}
+
+#[cfg(feature = "std")]
+fn write<T: Serialize, F: Formatter>(value: &T, formatter: F) -> Result<String, serde_json::Error> {
+    let mut writer = Vec::with_capacity(128);
+    let mut ser = serde_json::Serializer::with_formatter(&mut writer, formatter);
+
+    value.serialize(&mut ser)?;
+
+    // serde_json only ever writes valid UTF-8.
+    Ok(String::from_utf8(writer).expect("serde_json wrote invalid UTF-8"))
+}
+
+/// Reformats compact JSON in the style used by the Ogmo editor when compact export
+/// is disabled.
+///
+/// Arrays and objects that only contain scalar values are written on a single line,
+/// and all other arrays and objects are written with one item per line, indented by
+/// two spaces.
+pub(crate) fn to_editor_style(compact: &str) -> String {
+    // The first pass works out which containers have other containers inside them,
+    // indexed by the order in which they are opened.
+    let mut nested = Vec::new();
+    let mut open = Vec::new();
+
+    for (c, in_string) in scan(compact) {
+        match c {
+            '[' | '{' if !in_string => {
+                if let Some(&parent) = open.last() {
+                    nested[parent] = true;
+                }
+
+                open.push(nested.len());
+                nested.push(false);
+            }
+            ']' | '}' if !in_string => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let mut output = String::with_capacity(compact.len() * 2);
+    let mut expanded = Vec::new();
+    let mut containers = nested.into_iter();
+
+    let newline = |output: &mut String, depth: usize| {
+        output.push('\n');
+
+        for _ in 0..depth {
+            output.push_str("  ");
+        }
+    };
+
+    for (c, in_string) in scan(compact) {
+        if in_string {
+            output.push(c);
+            continue;
+        }
+
+        match c {
+            '[' | '{' => {
+                let is_expanded = containers.next().unwrap_or(false);
+
+                output.push(c);
+                expanded.push(is_expanded);
+
+                if is_expanded {
+                    newline(&mut output, expanded.len());
+                }
+            }
+            ']' | '}' => {
+                if expanded.pop().unwrap_or(false) {
+                    newline(&mut output, expanded.len());
+                }
+
+                output.push(c);
+            }
+            ',' => {
+                output.push(',');
+
+                if expanded.last().copied().unwrap_or(false) {
+                    newline(&mut output, expanded.len());
+                } else {
+                    output.push(' ');
+                }
+            }
+            ':' => output.push_str(": "),
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Iterates over the characters of a JSON string, along with whether each one is
+/// part of a string literal.
+fn scan(json: &str) -> impl Iterator<Item = (char, bool)> + '_ {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    json.chars().map(move |c| {
+        if !in_string {
+            // The opening quote counts as part of the string.
+            in_string = c == '"';
+            return (c, in_string);
+        }
+
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_string = false;
+        }
+
+        (c, true)
+    })
+}
+
+/// Serializes a map with its keys in sorted order, so that the output is the same
+/// between runs.
+#[cfg(not(feature = "preserve-order"))]
+pub(crate) fn serialize_sorted<S, V>(
+    map: &ValueMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Serializes a map in the order that its entries were parsed or inserted in, which
+/// is already the same between runs.
+#[cfg(feature = "preserve-order")]
+pub(crate) fn serialize_sorted<S, V>(
+    map: &ValueMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    map.serialize(serializer)
+}
+
+/// Serializes an optional map in the same order as `serialize_sorted`.
+pub(crate) fn serialize_sorted_opt<S, V>(
+    map: &Option<ValueMap<String, V>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    match map {
+        Some(map) => serialize_sorted(map, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// A buffered copy of a serialized value, with the keys of every map in sorted order.
+///
+/// This is used rather than `serde_json::Value`, as converting to a `Value` widens
+/// every f32 to an f64, which changes how it is written (`0.1` would become
+/// `0.10000000149011612`). `Value` also only sorts its keys if the `preserve_order`
+/// feature is disabled.
+enum Sorted {
+    Null,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Seq(Vec<Sorted>),
+    Map(BTreeMap<String, Sorted>),
+}
+
+impl Sorted {
+    /// Wraps a value in a single-entry map, in the same way that serde_json writes
+    /// enum variants that contain data.
+    fn variant(name: &'static str, value: Sorted) -> Sorted {
+        let mut map = BTreeMap::new();
+        map.insert(String::from(name), value);
+        Sorted::Map(map)
+    }
+}
+
+impl Serialize for Sorted {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Sorted::Null => serializer.serialize_unit(),
+            Sorted::Bool(v) => serializer.serialize_bool(*v),
+            Sorted::I64(v) => serializer.serialize_i64(*v),
+            Sorted::U64(v) => serializer.serialize_u64(*v),
+            Sorted::F32(v) => serializer.serialize_f32(*v),
+            Sorted::F64(v) => serializer.serialize_f64(*v),
+            Sorted::String(v) => serializer.serialize_str(v),
+            Sorted::Seq(items) => serializer.collect_seq(items),
+            Sorted::Map(map) => serializer.collect_map(map),
+        }
+    }
+}
+
+/// A serializer that buffers a value as a `Sorted`.
+struct SortedSerializer;
+
+impl Serializer for SortedSerializer {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    type SerializeSeq = SortedSeq;
+    type SerializeTuple = SortedSeq;
+    type SerializeTupleStruct = SortedSeq;
+    type SerializeTupleVariant = SortedSeq;
+    type SerializeMap = SortedMap;
+    type SerializeStruct = SortedMap;
+    type SerializeStructVariant = SortedMap;
+
+    fn serialize_bool(self, v: bool) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::I64(i64::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::I64(i64::from(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::I64(i64::from(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::U64(u64::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::U64(u64::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::U64(u64::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::U64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::Seq(
+            v.iter().map(|&b| Sorted::U64(u64::from(b))).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Sorted, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::String(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Sorted, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Sorted, Self::Error> {
+        Ok(Sorted::variant(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SortedSeq, Self::Error> {
+        Ok(SortedSeq {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SortedSeq, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SortedSeq, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SortedSeq, Self::Error> {
+        Ok(SortedSeq {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SortedMap, Self::Error> {
+        Ok(SortedMap {
+            variant: None,
+            map: BTreeMap::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SortedMap, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SortedMap, Self::Error> {
+        Ok(SortedMap {
+            variant: Some(variant),
+            map: BTreeMap::new(),
+            next_key: None,
+        })
+    }
+}
+
+/// Buffers the items of a sequence, tuple or tuple variant.
+struct SortedSeq {
+    variant: Option<&'static str>,
+    items: Vec<Sorted>,
+}
+
+impl SortedSeq {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        self.items.push(value.serialize(SortedSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Sorted {
+        let seq = Sorted::Seq(self.items);
+
+        match self.variant {
+            Some(variant) => Sorted::variant(variant, seq),
+            None => seq,
+        }
+    }
+}
+
+impl SerializeSeq for SortedSeq {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTuple for SortedSeq {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleStruct for SortedSeq {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleVariant for SortedSeq {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Buffers the entries of a map, struct or struct variant, sorted by key.
+struct SortedMap {
+    variant: Option<&'static str>,
+    map: BTreeMap<String, Sorted>,
+    next_key: Option<String>,
+}
+
+impl SortedMap {
+    fn insert<T: ?Sized + Serialize>(
+        &mut self,
+        key: String,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        self.map.insert(key, value.serialize(SortedSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Sorted {
+        let map = Sorted::Map(self.map);
+
+        match self.variant {
+            Some(variant) => Sorted::variant(variant, map),
+            None => map,
+        }
+    }
+}
+
+impl SerializeMap for SortedMap {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        // Like serde_json, keys can be strings or anything that is written as a scalar.
+        let key = match key.serialize(SortedSerializer)? {
+            Sorted::String(key) => key,
+            Sorted::Bool(key) => key.to_string(),
+            Sorted::I64(key) => key.to_string(),
+            Sorted::U64(key) => key.to_string(),
+            _ => return Err(SerError::custom("key must be a string")),
+        };
+
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| SerError::custom("value was serialized before its key"))?;
+
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStruct for SortedMap {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStructVariant for SortedMap {
+    type Ok = Sorted;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.insert(key.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Sorted, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Converts a float to an integer, if it is a whole number that can be written as
+/// one without losing precision.
+#[cfg(feature = "std")]
+fn as_whole(value: f64) -> Option<i64> {
+    if value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+/// Wraps another formatter, writing floats the way JavaScript's `JSON.stringify`
+/// does - Ogmo writes `16` rather than `16.0`.
+#[cfg(feature = "std")]
+struct OgmoFormatter<F>(F);
+
+#[cfg(feature = "std")]
+impl<F: Formatter> Formatter for OgmoFormatter<F> {
+    fn write_f32<W: ?Sized + Write>(&mut self, writer: &mut W, value: f32) -> io::Result<()> {
+        // Widening is only used to check for whole numbers - writing the widened value
+        // would lose the shortest form of the float (`0.1` would become
+        // `0.10000000149011612`).
+        match as_whole(f64::from(value)) {
+            Some(whole) => self.0.write_i64(writer, whole),
+            None => self.0.write_f32(writer, value),
+        }
+    }
+
+    fn write_f64<W: ?Sized + Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        match as_whole(value) {
+            Some(whole) => self.0.write_i64(writer, whole),
+            None => self.0.write_f64(writer, value),
+        }
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn end_object_key<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_key(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_value(writer)
+    }
+}
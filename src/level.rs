@@ -1,20 +1,45 @@
 //! Functions and types for parsing Ogmo levels.
 
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::convert::TryFrom;
+use core::fmt::{self, Display, Formatter};
+use core::iter::Enumerate;
+use core::slice;
+#[cfg(feature = "std")]
 use std::fs;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
 
 use either::Either;
-use hashbrown::HashMap;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::de::{DeserializeSeed, Error as DeError, MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
-use serde::{Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{Error, Vec2};
+use crate::project::{
+    cells_to_cover, resolve_values, validate_values, ArrayMode, EntityTemplate, GridLayerTemplate,
+    LayerTemplate, Project,
+};
+use crate::{
+    is_whole, json, Color, Error, HashSet, PrettyOptions, Rect, ValidationError, ValueMap, Vec2,
+};
 
 /// A dynamically typed value.
 ///
 /// As Ogmo's level format does not store the type alongside the value,
 /// it is not possible for this enum to specify the exact type of the
 /// original value template.
+///
+/// When comparing values, numbers are compared by their exact bit patterns, rather
+/// than via `==`. This means that `NaN` is equal to itself (so a value is always equal
+/// to a copy of itself), but `0.0` and `-0.0` are not equal. Values with different
+/// variants are never equal, even if they represent the same number.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Value {
@@ -24,16 +49,175 @@ pub enum Value {
     /// A string value.
     String(String),
 
-    /// A numeric value.
+    /// An integer value.
     ///
-    /// This may have originally been an integer when set, but the Ogmo
-    /// format does not provide enough information to figure that out
-    /// without cross-referencing the project.
+    /// This is used for any number that was written without a fractional part,
+    /// so that it will be written back out in the same form. Note that this does
+    /// not necessarily mean that the value template is an integer - cross-reference
+    /// the project if you need to know that.
+    Integer(i64),
+
+    /// A numeric value with a fractional part.
     Number(f32),
 }
 
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a.to_bits() == b.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+/// Formats the value for display to a user.
+///
+/// Booleans are written as `true` or `false`, strings are written verbatim (without
+/// quotes), and whole numbers are written without a decimal point.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::String(value) => f.write_str(value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Value {
+    fn mismatch(&self, expected: &str) -> Error {
+        Error::Inconsistent(format!("expected {}, found {:?}", expected, self))
+    }
+}
+
+/// Extracts a boolean from a `Value::Boolean`.
+///
+/// # Errors
+///
+/// * `Error::Inconsistent` will be returned if the value is not a boolean.
+impl TryFrom<&Value> for bool {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<bool, Error> {
+        match value {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(value.mismatch("a boolean")),
+        }
+    }
+}
+
+/// Extracts a float from a `Value::Number` or a `Value::Integer`.
+///
+/// Integers that are too large to be represented exactly as an `f32` will be rounded.
+///
+/// # Errors
+///
+/// * `Error::Inconsistent` will be returned if the value is not a number.
+impl TryFrom<&Value> for f32 {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<f32, Error> {
+        match value {
+            Value::Number(n) => Ok(*n),
+            Value::Integer(i) => Ok(*i as f32),
+            _ => Err(value.mismatch("a number")),
+        }
+    }
+}
+
+/// Extracts an integer from a `Value::Integer` or a `Value::Number`.
+///
+/// A `Value::Number` is truncated towards zero (so `1.9` becomes `1`), saturating at
+/// the bounds of `i32`. Use `ValueTemplate::resolve` if you only want to accept whole
+/// numbers.
+///
+/// # Errors
+///
+/// * `Error::Inconsistent` will be returned if the value is not a number, or if it is
+///   a `Value::Integer` that does not fit in an `i32`.
+impl TryFrom<&Value> for i32 {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<i32, Error> {
+        match value {
+            Value::Integer(i) => i32::try_from(*i).map_err(|_| value.mismatch("a 32-bit integer")),
+            Value::Number(n) => Ok(*n as i32),
+            _ => Err(value.mismatch("a number")),
+        }
+    }
+}
+
+/// Extracts a string from a `Value::String`.
+///
+/// # Errors
+///
+/// * `Error::Inconsistent` will be returned if the value is not a string.
+impl TryFrom<&Value> for String {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<String, Error> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            _ => Err(value.mismatch("a string")),
+        }
+    }
+}
+
+/// A custom value that has been resolved against its value template, so that its
+/// exact type is known.
+///
+/// These can be obtained from a level via `Level::from_json_with_project` or
+/// `Level::into_typed`, or from an individual `Value` via `ValueTemplate::resolve`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypedValue {
+    /// A boolean value.
+    Boolean(bool),
+
+    /// A color value.
+    Color(Color),
+
+    /// An enum value, represented by the label of the selected choice.
+    Enum(String),
+
+    /// An integer value.
+    Integer(i32),
+
+    /// A float value.
+    Float(f32),
+
+    /// A single-line string value.
+    String(String),
+
+    /// A multi-line text value.
+    Text(String),
+}
+
+impl TypedValue {
+    /// Converts the value back into the untyped form that is stored in level files.
+    ///
+    /// Colors are written as `#rrggbbaa` hex strings, and enums as the label of the
+    /// selected choice.
+    pub fn to_value(&self) -> Value {
+        match self {
+            TypedValue::Boolean(value) => Value::Boolean(*value),
+            TypedValue::Color(color) => Value::String(color.to_hex_string()),
+            TypedValue::Enum(label) => Value::String(label.clone()),
+            TypedValue::Integer(value) => Value::Integer(i64::from(*value)),
+            TypedValue::Float(value) => Value::Number(*value),
+            TypedValue::String(value) | TypedValue::Text(value) => Value::String(value.clone()),
+        }
+    }
+}
+
 /// An Ogmo level.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+///
+/// When comparing levels, custom values are compared as described on `Value`, and
+/// all other floats (such as positions and offsets) are compared via `==`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Level {
     /// The version of Ogmo used to export this level.
@@ -46,27 +230,79 @@ pub struct Level {
     pub height: f32,
 
     /// The offset of the level on the X axis. Useful for loading multiple chunked levels.
+    #[serde(default)]
     pub offset_x: f32,
 
     /// The offset of the level on the Y axis. Useful for loading multiple chunked levels.
+    #[serde(default)]
     pub offset_y: f32,
 
     /// The level's custom values.
-    #[serde(default)]
-    pub values: HashMap<String, Value>,
+    ///
+    /// Like the editor, this is omitted from the output if it is empty.
+    #[serde(
+        default,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub values: ValueMap<String, Value>,
 
     /// The layers that make up the level.
+    #[serde(deserialize_with = "deserialize_layers")]
     pub layers: Vec<Layer>,
+
+    /// Fields that this crate does not recognize.
+    ///
+    /// These (and the `extra` fields on the crate's other types) are kept so that they
+    /// can be written back out. They are written after the known fields, with their
+    /// keys in sorted order - enable the `preserve-order` feature to keep the order
+    /// they were parsed in instead.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 impl Level {
     /// Parses an Ogmo level from a JSON string.
     ///
+    /// A leading UTF-8 byte order mark is skipped, if there is one.
+    ///
     /// # Errors
     ///
     /// * `Error::Json` will be returned if deserialization fails.
     pub fn from_json(s: &str) -> Result<Level, Error> {
-        serde_json::from_str(s).map_err(Error::Json)
+        Level::from_slice(s.as_bytes())
+    }
+
+    /// Parses an Ogmo level from a JSON string, and resolves its custom values against
+    /// the given project.
+    ///
+    /// See `Level::into_typed` for details of how the values are resolved.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if deserialization fails.
+    /// * `Error::Inconsistent` or `Error::InvalidColor` will be returned if a value
+    ///   could not be resolved.
+    pub fn from_json_with_project(s: &str, project: &Project) -> Result<TypedLevel, Error> {
+        Level::from_json(s)?.into_typed(project)
+    }
+
+    /// Parses an Ogmo level from a slice of JSON bytes.
+    ///
+    /// This can be used to parse data that was loaded by other means, such as an
+    /// asynchronous file API, without converting it to a string first.
+    ///
+    /// A leading UTF-8 byte order mark is skipped, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if deserialization fails.
+    pub fn from_slice(v: &[u8]) -> Result<Level, Error> {
+        serde_json::from_slice(json::strip_bom(v)).map_err(Error::Json)
     }
 
     /// Parses an Ogmo level from a file.
@@ -75,18 +311,36 @@ impl Level {
     ///
     /// * `Error::Io` will be returned if the file cannot be read.
     /// * `Error::Json` will be returned if deserialization fails.
+    #[cfg(feature = "std")]
     pub fn from_file(path: impl AsRef<Path>) -> Result<Level, Error> {
-        let json = fs::read_to_string(path).map_err(Error::Io)?;
+        let json = fs::read_to_string(path)?;
         Level::from_json(&json)
     }
 
+    /// Parses an Ogmo level from a file, without blocking the async runtime.
+    ///
+    /// The file is read via `tokio::fs`, and then parsed on tokio's blocking thread pool.
+    /// This must be called from within a tokio runtime.
+    ///
+    /// This is only available if the `tokio` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Io` will be returned if the file cannot be read.
+    /// * `Error::Json` will be returned if deserialization fails.
+    #[cfg(feature = "tokio")]
+    pub async fn from_file_async(path: impl AsRef<Path>) -> Result<Level, Error> {
+        let json = tokio::fs::read(path.as_ref().to_path_buf()).await?;
+        crate::parse_blocking(move || Level::from_slice(&json)).await
+    }
+
     /// Writes the Ogmo level to a JSON string.
     ///
     /// # Errors
     ///
     /// * `Error::Json` will be returned if serialization fails.
     pub fn to_json(&self) -> Result<String, Error> {
-        serde_json::to_string(self).map_err(Error::Json)
+        json::to_string(self).map_err(Error::Json)
     }
 
     /// Writes the Ogmo level to a pretty-printed JSON string.
@@ -95,245 +349,2929 @@ impl Level {
     ///
     /// * `Error::Json` will be returned if serialization fails.
     pub fn to_json_pretty(&self) -> Result<String, Error> {
-        serde_json::to_string_pretty(self).map_err(Error::Json)
+        json::to_string_pretty(self).map_err(Error::Json)
     }
-}
 
-/// An entity instance.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Entity {
-    /// The entity's name.
-    pub name: String,
+    /// Writes the Ogmo level to a pretty-printed JSON string, with the given indentation
+    /// and key ordering.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if serialization fails.
+    pub fn to_json_pretty_with(&self, options: PrettyOptions) -> Result<String, Error> {
+        json::to_string_pretty_with(self, &options).map_err(Error::Json)
+    }
 
-    /// The entity's ID.
-    pub id: i32,
+    /// Writes the Ogmo level to a JSON string, formatted in the same way as the Ogmo
+    /// editor would format it for the given project.
+    ///
+    /// If the project's `compact_export` setting is enabled, the level is written
+    /// without any whitespace, the same as `to_json`. Otherwise, it is pretty-printed
+    /// in the editor's style, where arrays and objects that only contain numbers,
+    /// strings and booleans are kept on a single line.
+    ///
+    /// Compact export only affects whitespace - the editor does not omit any fields,
+    /// and the array and export modes of each layer are taken from how its data is
+    /// stored. If you want to omit fields that are set to their defaults, use
+    /// `to_json_minified` instead.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if serialization fails.
+    pub fn to_json_with_project(&self, project: &Project) -> Result<String, Error> {
+        let json = self.to_json()?;
 
-    /// The unique export ID of the entity.
-    #[serde(rename = "_eid")]
-    pub export_id: String,
+        if project.compact_export {
+            Ok(json)
+        } else {
+            Ok(json::to_editor_style(&json))
+        }
+    }
 
-    /// The X position of the entity.
-    pub x: f32,
+    /// Gets the highest tile ID used by any tile layer that uses the given tileset.
+    ///
+    /// Returns `None` if no tile layers use the tileset, or if they are all empty.
+    pub fn max_tile_id(&self, tileset_name: &str) -> Option<i32> {
+        self.layers
+            .iter()
+            .filter_map(|layer| match layer {
+                Layer::Tile(layer) if layer.tileset == tileset_name => Some(layer),
+                _ => None,
+            })
+            .flat_map(|layer| layer.unpack())
+            .filter_map(|tile| tile.id)
+            .max()
+    }
 
-    /// The Y position of the entity.
-    pub y: f32,
+    /// Writes the Ogmo level to a JSON string, omitting fields that are set to their
+    /// default values.
+    ///
+    /// This produces smaller output than `to_json`, for use as a runtime asset. The
+    /// following fields are omitted:
+    ///
+    /// * The level's `offsetX` and `offsetY`, if they are zero.
+    /// * Each layer's `offsetX` and `offsetY`, if they are zero.
+    ///
+    /// All of these fields will be restored to their default values when the level is
+    /// parsed again by this crate, but the output may not be readable by the editor.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if serialization fails.
+    pub fn to_json_minified(&self) -> Result<String, Error> {
+        // Converting the level straight to a `serde_json::Value` would widen every f32
+        // to an f64, so write it out in the usual format first.
+        let mut json: serde_json::Value = serde_json::from_str(&json::to_string(self)?)?;
+
+        if let Some(level) = json.as_object_mut() {
+            remove_zero_offsets(level);
+
+            if let Some(layers) = level.get_mut("layers").and_then(|l| l.as_array_mut()) {
+                for layer in layers.iter_mut().filter_map(|l| l.as_object_mut()) {
+                    remove_zero_offsets(layer);
+                }
+            }
+        }
 
-    /// The width of the entity.
-    /// Will only be present if the entity template was defined as resizable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub width: Option<f32>,
+        json::to_string(&json).map_err(Error::Json)
+    }
 
-    /// The width of the entity.
-    /// Will only be present if the entity template was defined as resizable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub height: Option<f32>,
+    /// Converts the level's cell data into a layered tensor, indexed as `[layer][y][x]`.
+    ///
+    /// One entry is produced for each tile layer and grid layer, in the order they
+    /// appear in the level. Tile co-ords, entity and decal layers are skipped, as their
+    /// data cannot be represented as a single number per cell.
+    ///
+    /// Every layer uses `-1` to represent an empty cell:
+    ///
+    /// * Tile layers are encoded as their tile IDs.
+    /// * Grid layers are encoded as the index of the cell's value in the matching
+    ///   template's legend, with the keys sorted and `"0"` (empty) excluded. Cells
+    ///   which are empty, or which have a value that is not in the legend, are
+    ///   encoded as `-1`.
+    ///
+    /// The project is required in order to look up the grid layer legends.
+    pub fn to_tensor(&self, project: &Project) -> Vec<Vec<Vec<i32>>> {
+        let mut tensor = Vec::new();
+
+        for layer in &self.layers {
+            match layer {
+                Layer::Tile(layer) => {
+                    let mut cells = empty_tensor_layer(layer.grid_cells_x, layer.grid_cells_y);
+
+                    for tile in layer.unpack() {
+                        if let Some(id) = tile.id {
+                            set_tensor_cell(&mut cells, tile.grid_position, id);
+                        }
+                    }
 
-    /// The X origin of the entity.
-    /// Will only be present if the entity template defined an origin.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub origin_x: Option<f32>,
+                    tensor.push(cells);
+                }
+
+                Layer::Grid(layer) => {
+                    let mut keys: Vec<&str> = project
+                        .layers
+                        .iter()
+                        .find_map(|t| match t {
+                            LayerTemplate::Grid(t) if t.export_id == layer.export_id => {
+                                Some(t.legend.keys().map(String::as_str).collect())
+                            }
+                            _ => None,
+                        })
+                        .unwrap_or_default();
+
+                    keys.retain(|&k| k != "0");
+                    keys.sort_unstable();
+
+                    let mut cells = empty_tensor_layer(layer.grid_cells_x, layer.grid_cells_y);
+
+                    for cell in layer.unpack() {
+                        if let Some(i) = keys.iter().position(|&k| k == cell.value) {
+                            set_tensor_cell(&mut cells, cell.grid_position, i as i32);
+                        }
+                    }
 
-    /// The Y origin of the entity.
-    /// Will only be present if the entity template defined an origin.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub origin_y: Option<f32>,
+                    tensor.push(cells);
+                }
 
-    /// The rotation of the entity.
-    /// Will only be present if the entity template was defined as rotatable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rotation: Option<f32>,
+                _ => {}
+            }
+        }
 
-    /// Whether the entity is flipped on the X axis.
-    /// Will only be present if the entity template was defined as X-flippable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub flipped_x: Option<bool>,
+        tensor
+    }
 
-    /// Whether the entity is flipped on the Y axis.
-    /// Will only be present if the entity template was defined as Y-flippable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub flipped_y: Option<bool>,
+    /// Unpacks the data for every layer in the level up front.
+    ///
+    /// If the `rayon` feature is enabled, the layers will be unpacked in parallel.
+    pub fn unpack_all(&self) -> UnpackedLevel<'_> {
+        #[cfg(feature = "rayon")]
+        let layers = self.layers.par_iter().map(UnpackedLayer::new).collect();
 
-    /// The entity's nodes.
-    /// Will only be present if the entity template was defined as having nodes.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub nodes: Option<Vec<Vec2<f32>>>,
+        #[cfg(not(feature = "rayon"))]
+        let layers = self.layers.iter().map(UnpackedLayer::new).collect();
 
-    /// The entity's custom values.
-    /// Will only be present if the entity template was defined as having custom values.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub values: Option<HashMap<String, Value>>,
-}
+        UnpackedLevel { layers }
+    }
 
-/// A decal instance.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Decal {
-    /// The X position of the decal.
+    /// Flattens the level into a list of items that can be drawn, in the order that
+    /// their layers appear in the level.
     ///
-    /// Decals have their origin at the center of the image, so you may need
-    /// to correct for this when rendering.
-    pub x: f32,
-
-    /// The Y position of the decal.
+    /// This is a quick way of getting from a level to renderable data, and does not
+    /// depend on any particular engine. The following conversions are made:
     ///
-    /// Decals have their origin at the center of the image, so you may need
-    /// to correct for this when rendering.
-    pub y: f32,
+    /// * Empty tiles are skipped, and each tile's tileset is given as an index into the
+    ///   project's `tilesets`.
+    /// * Grid cells with a value of `"0"` (empty, by default) are skipped.
+    /// * Layers of an unknown type are skipped.
+    /// * Decal textures are given as a path relative to the project, and their rotations
+    ///   are converted to radians.
+    ///
+    /// All positions include both the layer's offset and the level's offset. For more
+    /// control over the output (or better performance), unpack the layers yourself.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if a layer refers to a tileset that is
+    ///   not in the project.
+    pub fn flatten<'a>(&'a self, project: &Project) -> Result<Vec<FlatItem<'a>>, Error> {
+        let mut items = Vec::new();
+        let world = self.world_offset();
+
+        let tileset_index = |name: &str| {
+            project
+                .tilesets
+                .iter()
+                .position(|t| t.label == name)
+                .ok_or_else(|| Error::Inconsistent(format!("unknown tileset '{}'", name)))
+        };
+
+        let layer_position = |offset_x: f32, offset_y: f32, x: f32, y: f32| Vec2 {
+            x: x + offset_x + world.x,
+            y: y + offset_y + world.y,
+        };
+
+        for layer in &self.layers {
+            match layer {
+                Layer::Tile(layer) => {
+                    let tileset = tileset_index(&layer.tileset)?;
+
+                    items.extend(layer.unpack_with_world_offset(world).filter_map(|tile| {
+                        Some(FlatItem::Tile {
+                            tileset,
+                            id: tile.id?,
+                            position: tile.pixel_position.map(|v| v as f32),
+                        })
+                    }));
+                }
+
+                Layer::TileCoords(layer) => {
+                    let tileset = tileset_index(&layer.tileset)?;
+
+                    items.extend(layer.unpack_with_world_offset(world).filter_map(|tile| {
+                        let uv = tile.pixel_coords?;
+
+                        Some(FlatItem::TileUv {
+                            tileset,
+                            uv: Rect {
+                                x: uv.x,
+                                y: uv.y,
+                                width: layer.grid_cell_width,
+                                height: layer.grid_cell_height,
+                            },
+                            position: tile.pixel_position.map(|v| v as f32),
+                        })
+                    }));
+                }
+
+                Layer::Grid(layer) => {
+                    items.extend(
+                        layer
+                            .unpack_with_world_offset(world)
+                            .filter(|cell| cell.value != "0")
+                            .map(|cell| FlatItem::Grid {
+                                rect: Rect {
+                                    x: cell.pixel_position.x as f32,
+                                    y: cell.pixel_position.y as f32,
+                                    width: layer.grid_cell_width as f32,
+                                    height: layer.grid_cell_height as f32,
+                                },
+                                value: cell.value,
+                            }),
+                    );
+                }
+
+                Layer::Entity(layer) => {
+                    items.extend(layer.entities.iter().map(|entity| FlatItem::Entity {
+                        entity,
+                        position: layer_position(
+                            layer.offset_x,
+                            layer.offset_y,
+                            entity.x,
+                            entity.y,
+                        ),
+                    }));
+                }
+
+                Layer::Decal(layer) => {
+                    items.extend(layer.decals.iter().map(|decal| FlatItem::Decal {
+                        texture: if layer.folder.is_empty() {
+                            decal.texture.clone()
+                        } else {
+                            format!("{}/{}", layer.folder, decal.texture)
+                        },
+                        transform: DecalTransform {
+                            position: layer_position(
+                                layer.offset_x,
+                                layer.offset_y,
+                                decal.x,
+                                decal.y,
+                            ),
+                            rotation: project.to_radians(decal.rotation.unwrap_or(0.0)),
+                            scale: Vec2 {
+                                x: decal.scale_x.unwrap_or(1.0),
+                                y: decal.scale_y.unwrap_or(1.0),
+                            },
+                        },
+                    }));
+                }
 
-    /// The scale of the decal on the X axis.
-    /// Will only be present if the decal template was defined as scalable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub scale_x: Option<f32>,
+                Layer::Unknown(_) => {}
+            }
+        }
 
-    /// The scale of the decal on the Y axis.
-    /// Will only be present if the decal template was defined as scalable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub scale_y: Option<f32>,
+        Ok(items)
+    }
 
-    /// The rotation of the decal.
-    /// Will only be present if the decal template was defined as rotatable.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rotation: Option<f32>,
+    /// Gets the extent of the level's content, as a pair of minimum and maximum corners.
+    ///
+    /// This is the union of every non-empty tile and grid cell (with a value other
+    /// than `"0"`), every entity and every decal position, with each layer's offset
+    /// applied. It can be smaller or larger than the declared `width` and `height`,
+    /// which makes it useful for framing a camera or trimming empty space.
+    ///
+    /// Entities use their own size and origin when they are set - as the template isn't
+    /// available, an entity without a size is treated as a single point. Layers of an
+    /// unknown type are skipped, and the level's own `offset_x` and `offset_y` are not
+    /// applied.
+    ///
+    /// If the level has no content, the box from `(0, 0)` to `(width, height)` is
+    /// returned.
+    pub fn content_bounds(&self) -> (Vec2<f32>, Vec2<f32>) {
+        let mut bounds: Option<(Vec2<f32>, Vec2<f32>)> = None;
+
+        let mut include = |min: Vec2<f32>, max: Vec2<f32>| {
+            let (lo, hi) = bounds.get_or_insert((min, max));
+
+            lo.x = lo.x.min(min.x);
+            lo.y = lo.y.min(min.y);
+            hi.x = hi.x.max(max.x);
+            hi.y = hi.y.max(max.y);
+        };
+
+        let cell_bounds = |position: Vec2<i32>, cell_width: i32, cell_height: i32| {
+            let min = position.map(|v| v as f32);
+
+            let max = Vec2 {
+                x: min.x + cell_width as f32,
+                y: min.y + cell_height as f32,
+            };
+
+            (min, max)
+        };
+
+        for layer in &self.layers {
+            match layer {
+                Layer::Tile(layer) => {
+                    for tile in layer.unpack_with_offset().filter(|t| t.id.is_some()) {
+                        let (min, max) = cell_bounds(
+                            tile.pixel_position,
+                            layer.grid_cell_width,
+                            layer.grid_cell_height,
+                        );
+
+                        include(min, max);
+                    }
+                }
+
+                Layer::TileCoords(layer) => {
+                    for tile in layer
+                        .unpack_with_offset()
+                        .filter(|t| t.pixel_coords.is_some())
+                    {
+                        let (min, max) = cell_bounds(
+                            tile.pixel_position,
+                            layer.grid_cell_width,
+                            layer.grid_cell_height,
+                        );
+
+                        include(min, max);
+                    }
+                }
 
-    /// The name of the decal's texture.
-    pub texture: String,
+                Layer::Grid(layer) => {
+                    for cell in layer.unpack_with_offset().filter(|c| c.value != "0") {
+                        let (min, max) = cell_bounds(
+                            cell.pixel_position,
+                            layer.grid_cell_width,
+                            layer.grid_cell_height,
+                        );
 
-    /// Custom values associated with the decal.
-    pub values: HashMap<String, Value>,
-}
+                        include(min, max);
+                    }
+                }
 
-/// A layer instance.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(untagged)]
-pub enum Layer {
-    /// A tile layer.
-    Tile(TileLayer),
+                Layer::Entity(layer) => {
+                    for entity in &layer.entities {
+                        let min = Vec2 {
+                            x: entity.x - entity.origin_x.unwrap_or(0.0) + layer.offset_x,
+                            y: entity.y - entity.origin_y.unwrap_or(0.0) + layer.offset_y,
+                        };
 
-    /// A tile co-ords layer.
-    TileCoords(TileCoordsLayer),
+                        let max = Vec2 {
+                            x: min.x + entity.width.unwrap_or(0.0),
+                            y: min.y + entity.height.unwrap_or(0.0),
+                        };
 
-    /// A grid layer.
-    Grid(GridLayer),
+                        include(min, max);
+                    }
+                }
 
-    /// An entity layer.
-    Entity(EntityLayer),
+                Layer::Decal(layer) => {
+                    for decal in &layer.decals {
+                        let position = Vec2 {
+                            x: decal.x + layer.offset_x,
+                            y: decal.y + layer.offset_y,
+                        };
 
-    /// A decal layer.
-    Decal(DecalLayer),
-}
+                        include(position, position);
+                    }
+                }
 
-impl Layer {
-    /// Gets the name of the layer.
-    pub fn name(&self) -> &str {
-        match self {
-            Layer::Tile(data) => &data.name,
-            Layer::TileCoords(data) => &data.name,
-            Layer::Grid(data) => &data.name,
-            Layer::Entity(data) => &data.name,
-            Layer::Decal(data) => &data.name,
+                Layer::Unknown(_) => {}
+            }
+        }
+
+        bounds.unwrap_or((
+            Vec2 { x: 0.0, y: 0.0 },
+            Vec2 {
+                x: self.width,
+                y: self.height,
+            },
+        ))
+    }
+
+    /// Converts the data of every tile, tile co-ords and grid layer in the level to the
+    /// given storage mode.
+    ///
+    /// This can be used to make a level's output match the project's `compact_export`
+    /// setting, or the array mode of its layer templates.
+    ///
+    /// When converting to 1D, any 2D rows that are shorter than `grid_cells_x` will be
+    /// padded with empty cells, so that the positions of the cells are preserved.
+    pub fn set_storage_mode(&mut self, mode: ArrayMode) {
+        for layer in &mut self.layers {
+            match layer {
+                Layer::Tile(layer) => {
+                    let cells_x = layer.grid_cells_x.max(0) as usize;
+
+                    layer.data = match mode {
+                        ArrayMode::One => layer.data.to_1d(cells_x),
+                        ArrayMode::Two => layer.data.to_2d(cells_x),
+                    };
+                }
+
+                Layer::TileCoords(layer) => {
+                    let cells_x = layer.grid_cells_x.max(0) as usize;
+
+                    layer.data = match mode {
+                        ArrayMode::One => layer.data.to_1d(cells_x),
+                        ArrayMode::Two => layer.data.to_2d(cells_x),
+                    };
+                }
+
+                Layer::Grid(layer) => {
+                    let cells_x = layer.grid_cells_x.max(0) as usize;
+
+                    layer.data = match mode {
+                        ArrayMode::One => layer.data.to_1d(cells_x),
+                        ArrayMode::Two => layer.data.to_2d(cells_x),
+                    };
+                }
+
+                Layer::Entity(_) | Layer::Decal(_) | Layer::Unknown(_) => {}
+            }
+        }
+    }
+
+    /// Checks that the level was exported by a version of Ogmo that this crate supports.
+    ///
+    /// The version is not checked when a level is loaded, so call this if you want to reject
+    /// levels from incompatible versions of the editor with a clear error.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsupportedVersion` will be returned if the level's major version is not supported.
+    pub fn check_version(&self) -> Result<(), Error> {
+        crate::check_version(&self.ogmo_version)
+    }
+
+    /// Checks that the level is consistent with the given project.
+    ///
+    /// The following checks are made:
+    ///
+    /// * Each layer's `export_id` matches one of the project's layer templates.
+    /// * Each tile and tile co-ords layer's tileset exists in the project.
+    /// * Each tile layer's data matches its grid size (see `TileLayer::check_dimensions`).
+    /// * Each tile co-ords layer's data is well-formed (see `TileCoordsLayer::validate`).
+    /// * Each entity's name matches one of the project's entity templates.
+    /// * The level's custom values, and those of its entities, are valid for their
+    ///   value templates (see `ValueTemplate::validate`). Values without a matching
+    ///   template are not checked.
+    ///
+    /// # Errors
+    ///
+    /// If any of the checks fail, a list of every error that was found will be returned.
+    pub fn validate(&self, project: &Project) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        for layer in &self.layers {
+            if project
+                .layer_template_by_export_id(layer.export_id())
+                .is_none()
+            {
+                errors.push(ValidationError::UnknownLayerTemplate {
+                    layer: layer.name().to_owned(),
+                    export_id: layer.export_id().to_owned(),
+                });
+            }
+
+            let tileset = match layer {
+                Layer::Tile(layer) => layer.tileset_name(),
+                Layer::TileCoords(layer) => layer.tileset_name(),
+                _ => None,
+            };
+
+            if let Some(tileset) = tileset {
+                if project.tileset_by_label(tileset).is_none() {
+                    errors.push(ValidationError::UnknownTileset {
+                        layer: layer.name().to_owned(),
+                        tileset: tileset.to_owned(),
+                    });
+                }
+            }
+
+            if let Layer::Tile(tile_layer) = layer {
+                if let Some(message) = tile_layer.dimension_mismatch() {
+                    errors.push(ValidationError::MismatchedDimensions {
+                        layer: layer.name().to_owned(),
+                        message,
+                    });
+                }
+            }
+
+            if let Layer::TileCoords(tile_coords_layer) = layer {
+                if let Err(malformed) = tile_coords_layer.validate() {
+                    errors.extend(malformed);
+                }
+            }
+
+            if let Layer::Entity(entity_layer) = layer {
+                for entity in &entity_layer.entities {
+                    match project.entity_template_by_name(&entity.name) {
+                        Some(template) => {
+                            if let Some(values) = &entity.values {
+                                errors.extend(validate_values(values, &template.values));
+                            }
+                        }
+
+                        None => errors.push(ValidationError::UnknownEntityTemplate {
+                            layer: layer.name().to_owned(),
+                            entity: entity.name.clone(),
+                        }),
+                    }
+                }
+            }
+        }
+
+        errors.extend(validate_values(&self.values, &project.level_values));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns an iterator over every entity in the level, across all entity layers.
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        self.layers
+            .iter()
+            .filter_map(Layer::as_entity)
+            .flat_map(|layer| &layer.entities)
+    }
+
+    /// Returns an iterator over every decal in the level, across all decal layers.
+    pub fn decals(&self) -> impl Iterator<Item = &Decal> {
+        self.layers
+            .iter()
+            .filter_map(Layer::as_decal)
+            .flat_map(|layer| &layer.decals)
+    }
+
+    /// Returns an iterator over the level's custom values, sorted by name.
+    ///
+    /// The order of `values` depends on the map's hasher, so this is useful for
+    /// producing stable output, such as in logs or debug dumps.
+    pub fn values_sorted(&self) -> impl Iterator<Item = (&str, &Value)> {
+        sorted_values(&self.values)
+    }
+
+    /// Gets the level's offset within the wider world, as set by `offset_x` and
+    /// `offset_y`.
+    ///
+    /// This can be passed to the `unpack_with_world_offset` methods on each layer, to
+    /// get the absolute positions of the layer's contents.
+    pub fn world_offset(&self) -> Vec2<f32> {
+        Vec2 {
+            x: self.offset_x,
+            y: self.offset_y,
+        }
+    }
+
+    /// Returns an iterator over every non-empty tile in the level, across all tile
+    /// layers.
+    ///
+    /// Each tile is paired with the layer that it belongs to, so that its tileset can
+    /// be looked up. The positions of the tiles do not include the layer's offset.
+    pub fn tiles(&self) -> impl Iterator<Item = (&TileLayer, Tile)> {
+        self.layers
+            .iter()
+            .filter_map(Layer::as_tile)
+            .flat_map(|layer| {
+                layer
+                    .unpack()
+                    .filter(|tile| tile.id.is_some())
+                    .map(move |tile| (layer, tile))
+            })
+    }
+
+    /// Merges several chunked levels into a single level, using the levels' offsets to
+    /// position them relative to each other.
+    ///
+    /// The merged level's offset and size will cover all of the given levels. Its
+    /// version and custom values are taken from the first level.
+    ///
+    /// Layers are matched up by their export ID, in the order that they first appear:
+    ///
+    /// * Tile, tile co-ords and grid layers are combined into a single, larger grid,
+    ///   with the layer's offset set to the top left corner of that grid. Where chunks
+    ///   overlap, non-empty cells from later levels take priority.
+    /// * Entity and decal layers have their instances translated by the level and layer
+    ///   offsets, and then appended to a single layer with no offset.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if no levels are given, if layers with
+    ///   the same export ID have different types, grid sizes or tilesets, if a layer
+    ///   is not aligned to the grid of the merged layer, or if a layer is of an unknown type.
+    pub fn merged(levels: &[Level]) -> Result<Level, Error> {
+        let first = levels
+            .first()
+            .ok_or_else(|| Error::Inconsistent("no levels were given to merge".into()))?;
+
+        let min_x = levels
+            .iter()
+            .map(|l| l.offset_x)
+            .fold(f32::INFINITY, f32::min);
+        let min_y = levels
+            .iter()
+            .map(|l| l.offset_y)
+            .fold(f32::INFINITY, f32::min);
+        let max_x = levels
+            .iter()
+            .map(|l| l.offset_x + l.width)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let max_y = levels
+            .iter()
+            .map(|l| l.offset_y + l.height)
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        let mut groups: Vec<Vec<(Vec2<f32>, &Layer)>> = Vec::new();
+
+        for level in levels {
+            let delta = Vec2 {
+                x: level.offset_x - min_x,
+                y: level.offset_y - min_y,
+            };
+
+            for layer in &level.layers {
+                match groups
+                    .iter_mut()
+                    .find(|parts| parts[0].1.export_id() == layer.export_id())
+                {
+                    Some(parts) => parts.push((delta, layer)),
+                    None => groups.push(vec![(delta, layer)]),
+                }
+            }
+        }
+
+        let width = max_x - min_x;
+        let height = max_y - min_y;
+
+        let layers = groups
+            .into_iter()
+            .map(|parts| merge_layers(&parts, width, height))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Level {
+            ogmo_version: first.ogmo_version.clone(),
+            width,
+            height,
+            offset_x: min_x,
+            offset_y: min_y,
+            values: first.values.clone(),
+            layers,
+            extra: first.extra.clone(),
+        })
+    }
+
+    /// Resolves the level's custom values, and the custom values of its entities,
+    /// against the given project.
+    ///
+    /// Level values are resolved using the project's `level_values`, and entity values
+    /// using the template with a matching name. Any values that are missing from the
+    /// level are filled in with the template's default.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if an entity has no matching template,
+    ///   if a value has no matching value template, or if a value does not match the
+    ///   type of its template.
+    /// * `Error::InvalidColor` will be returned if a color value is not a valid hex color.
+    pub fn into_typed(self, project: &Project) -> Result<TypedLevel, Error> {
+        let values = resolve_values(&self.values, &project.level_values)?;
+
+        let mut entity_values = ValueMap::new();
+
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, layer)| Some((i, layer.as_entity()?)));
+
+        for (layer_index, layer) in layers {
+            for (entity_index, entity) in layer.entities.iter().enumerate() {
+                let template = project
+                    .entity_template_by_name(&entity.name)
+                    .ok_or_else(|| {
+                        Error::Inconsistent(format!(
+                            "entity '{}' does not match any template",
+                            entity.name
+                        ))
+                    })?;
+
+                let values = match &entity.values {
+                    Some(values) => resolve_values(values, &template.values)?,
+                    None => resolve_values(&ValueMap::new(), &template.values)?,
+                };
+
+                entity_values.insert((layer_index, entity_index), values);
+            }
         }
+
+        Ok(TypedLevel {
+            level: self,
+            resolved_values: values.clone(),
+            resolved_entity_values: entity_values.clone(),
+            values,
+            entity_values,
+        })
     }
 }
 
-/// A tile layer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Merges layers that share an export ID into a single layer.
+///
+/// Each layer is paired with the offset of its level, relative to the merged level.
+fn merge_layers(parts: &[(Vec2<f32>, &Layer)], width: f32, height: f32) -> Result<Layer, Error> {
+    let export_id = parts[0].1.export_id();
+
+    let mismatch = |what: &str| {
+        Error::Inconsistent(format!(
+            "layers with export ID '{}' have different {}",
+            export_id, what
+        ))
+    };
+
+    match parts[0].1 {
+        Layer::Tile(first) => {
+            let mut chunks = Vec::new();
+
+            for (delta, layer) in parts {
+                let layer = match layer {
+                    Layer::Tile(layer) => layer,
+                    _ => return Err(mismatch("types")),
+                };
+
+                if layer.tileset != first.tileset {
+                    return Err(mismatch("tilesets"));
+                }
+
+                chunks.push(GridChunk {
+                    origin: Vec2 {
+                        x: delta.x + layer.offset_x,
+                        y: delta.y + layer.offset_y,
+                    },
+                    cell_size: Vec2 {
+                        x: layer.grid_cell_width,
+                        y: layer.grid_cell_height,
+                    },
+                    size: Vec2 {
+                        x: layer.grid_cells_x,
+                        y: layer.grid_cells_y,
+                    },
+                    cells: layer
+                        .unpack()
+                        .filter_map(|tile| Some((tile.grid_position, tile.id?)))
+                        .collect(),
+                });
+            }
+
+            let MergedGrid { offset, size, rows } = merge_chunks(export_id, chunks, -1)?;
+
+            Ok(Layer::Tile(TileLayer {
+                name: first.name.clone(),
+                export_id: first.export_id.clone(),
+                offset_x: offset.x,
+                offset_y: offset.y,
+                grid_cell_width: first.grid_cell_width,
+                grid_cell_height: first.grid_cell_height,
+                grid_cells_x: size.x,
+                grid_cells_y: size.y,
+                tileset: first.tileset.clone(),
+                data: match first.data {
+                    TileLayerStorage::Data(_) => TileLayerStorage::Data(rows.concat()),
+                    TileLayerStorage::Data2D(_) => TileLayerStorage::Data2D(rows),
+                },
+            }))
+        }
+
+        Layer::TileCoords(first) => {
+            let mut chunks = Vec::new();
+
+            for (delta, layer) in parts {
+                let layer = match layer {
+                    Layer::TileCoords(layer) => layer,
+                    _ => return Err(mismatch("types")),
+                };
+
+                if layer.tileset != first.tileset {
+                    return Err(mismatch("tilesets"));
+                }
+
+                chunks.push(GridChunk {
+                    origin: Vec2 {
+                        x: delta.x + layer.offset_x,
+                        y: delta.y + layer.offset_y,
+                    },
+                    cell_size: Vec2 {
+                        x: layer.grid_cell_width,
+                        y: layer.grid_cell_height,
+                    },
+                    size: Vec2 {
+                        x: layer.grid_cells_x,
+                        y: layer.grid_cells_y,
+                    },
+                    cells: layer
+                        .unpack()
+                        .filter_map(|tile| {
+                            let coords = tile.grid_coords?;
+                            Some((tile.grid_position, vec![coords.x, coords.y]))
+                        })
+                        .collect(),
+                });
+            }
+
+            let MergedGrid { offset, size, rows } =
+                merge_chunks(export_id, chunks, first.data.empty_coords())?;
+
+            Ok(Layer::TileCoords(TileCoordsLayer {
+                name: first.name.clone(),
+                export_id: first.export_id.clone(),
+                offset_x: offset.x,
+                offset_y: offset.y,
+                grid_cell_width: first.grid_cell_width,
+                grid_cell_height: first.grid_cell_height,
+                grid_cells_x: size.x,
+                grid_cells_y: size.y,
+                tileset: first.tileset.clone(),
+                data: match first.data {
+                    TileCoordsLayerStorage::DataCoords(_) => {
+                        TileCoordsLayerStorage::DataCoords(rows.concat())
+                    }
+                    TileCoordsLayerStorage::DataCoords2D(_) => {
+                        TileCoordsLayerStorage::DataCoords2D(rows)
+                    }
+                },
+            }))
+        }
+
+        Layer::Grid(first) => {
+            let mut chunks = Vec::new();
+
+            for (delta, layer) in parts {
+                let layer = match layer {
+                    Layer::Grid(layer) => layer,
+                    _ => return Err(mismatch("types")),
+                };
+
+                chunks.push(GridChunk {
+                    origin: Vec2 {
+                        x: delta.x + layer.offset_x,
+                        y: delta.y + layer.offset_y,
+                    },
+                    cell_size: Vec2 {
+                        x: layer.grid_cell_width,
+                        y: layer.grid_cell_height,
+                    },
+                    size: Vec2 {
+                        x: layer.grid_cells_x,
+                        y: layer.grid_cells_y,
+                    },
+                    cells: layer
+                        .unpack()
+                        .filter(|cell| cell.value != "0")
+                        .map(|cell| (cell.grid_position, cell.value.to_owned()))
+                        .collect(),
+                });
+            }
+
+            let MergedGrid { offset, size, rows } =
+                merge_chunks(export_id, chunks, "0".to_owned())?;
+
+            Ok(Layer::Grid(GridLayer {
+                name: first.name.clone(),
+                export_id: first.export_id.clone(),
+                offset_x: offset.x,
+                offset_y: offset.y,
+                grid_cell_width: first.grid_cell_width,
+                grid_cell_height: first.grid_cell_height,
+                grid_cells_x: size.x,
+                grid_cells_y: size.y,
+                data: match first.data {
+                    GridLayerStorage::Grid(_) => GridLayerStorage::Grid(rows.concat()),
+                    GridLayerStorage::Grid2D(_) => GridLayerStorage::Grid2D(rows),
+                },
+            }))
+        }
+
+        Layer::Entity(first) => {
+            let mut entities = Vec::new();
+
+            for (delta, layer) in parts {
+                let layer = match layer {
+                    Layer::Entity(layer) => layer,
+                    _ => return Err(mismatch("types")),
+                };
+
+                let dx = delta.x + layer.offset_x;
+                let dy = delta.y + layer.offset_y;
+
+                entities.extend(layer.entities.iter().cloned().map(|mut entity| {
+                    entity.x += dx;
+                    entity.y += dy;
+
+                    for node in entity.nodes.iter_mut().flatten() {
+                        node.x += dx;
+                        node.y += dy;
+                    }
+
+                    entity
+                }));
+            }
+
+            Ok(Layer::Entity(EntityLayer {
+                name: first.name.clone(),
+                export_id: first.export_id.clone(),
+                offset_x: 0.0,
+                offset_y: 0.0,
+                grid_cell_width: first.grid_cell_width,
+                grid_cell_height: first.grid_cell_height,
+                grid_cells_x: cells_to_cover(width, first.grid_cell_width),
+                grid_cells_y: cells_to_cover(height, first.grid_cell_height),
+                entities,
+            }))
+        }
+
+        Layer::Decal(first) => {
+            let mut decals = Vec::new();
+
+            for (delta, layer) in parts {
+                let layer = match layer {
+                    Layer::Decal(layer) => layer,
+                    _ => return Err(mismatch("types")),
+                };
+
+                let dx = delta.x + layer.offset_x;
+                let dy = delta.y + layer.offset_y;
+
+                decals.extend(layer.decals.iter().cloned().map(|mut decal| {
+                    decal.x += dx;
+                    decal.y += dy;
+                    decal
+                }));
+            }
+
+            Ok(Layer::Decal(DecalLayer {
+                name: first.name.clone(),
+                export_id: first.export_id.clone(),
+                offset_x: 0.0,
+                offset_y: 0.0,
+                grid_cell_width: first.grid_cell_width,
+                grid_cell_height: first.grid_cell_height,
+                grid_cells_x: cells_to_cover(width, first.grid_cell_width),
+                grid_cells_y: cells_to_cover(height, first.grid_cell_height),
+                decals,
+                folder: first.folder.clone(),
+            }))
+        }
+
+        Layer::Unknown(_) => Err(Error::Inconsistent(format!(
+            "layers with export ID '{}' are of an unknown type, and cannot be merged",
+            export_id
+        ))),
+    }
+}
+
+/// The cells of a grid-based layer that is being merged with others.
+struct GridChunk<T> {
+    /// The position of the layer's top left corner in the merged level, in pixels.
+    origin: Vec2<f32>,
+
+    /// The size of the layer's grid cells, in pixels.
+    cell_size: Vec2<i32>,
+
+    /// The number of grid cells in the layer.
+    size: Vec2<i32>,
+
+    /// The layer's non-empty cells, along with their grid positions.
+    cells: Vec<(Vec2<i32>, T)>,
+}
+
+/// The result of merging several `GridChunk`s.
+struct MergedGrid<T> {
+    /// The position of the grid's top left corner in the merged level, in pixels.
+    offset: Vec2<f32>,
+
+    /// The number of grid cells in the merged grid.
+    size: Vec2<i32>,
+
+    /// The merged grid's cells, split into rows.
+    rows: Vec<Vec<T>>,
+}
+
+/// Combines the cells of several layers into a single grid that covers all of them.
+fn merge_chunks<T: Clone>(
+    export_id: &str,
+    chunks: Vec<GridChunk<T>>,
+    empty: T,
+) -> Result<MergedGrid<T>, Error> {
+    let cell_size = chunks[0].cell_size;
+
+    if cell_size.x <= 0 || cell_size.y <= 0 || chunks.iter().any(|c| c.cell_size != cell_size) {
+        return Err(Error::Inconsistent(format!(
+            "layers with export ID '{}' have different or invalid grid sizes",
+            export_id
+        )));
+    }
+
+    let offset = Vec2 {
+        x: chunks
+            .iter()
+            .map(|c| c.origin.x)
+            .fold(f32::INFINITY, f32::min),
+        y: chunks
+            .iter()
+            .map(|c| c.origin.y)
+            .fold(f32::INFINITY, f32::min),
+    };
+
+    let mut positions = Vec::with_capacity(chunks.len());
+
+    for chunk in &chunks {
+        let x = (chunk.origin.x - offset.x) / cell_size.x as f32;
+        let y = (chunk.origin.y - offset.y) / cell_size.y as f32;
+
+        if !is_whole(x) || !is_whole(y) {
+            return Err(Error::Inconsistent(format!(
+                "a layer with export ID '{}' is not aligned to the merged grid",
+                export_id
+            )));
+        }
+
+        positions.push(Vec2 {
+            x: x as i32,
+            y: y as i32,
+        });
+    }
+
+    let size = Vec2 {
+        x: chunks
+            .iter()
+            .zip(&positions)
+            .map(|(c, p)| p.x + c.size.x.max(0))
+            .max()
+            .unwrap_or(0),
+        y: chunks
+            .iter()
+            .zip(&positions)
+            .map(|(c, p)| p.y + c.size.y.max(0))
+            .max()
+            .unwrap_or(0),
+    };
+
+    let row = vec![empty; size.x as usize];
+    let mut rows = vec![row; size.y as usize];
+
+    for (chunk, position) in chunks.into_iter().zip(positions) {
+        for (cell, value) in chunk.cells {
+            if in_grid(chunk.size.x, chunk.size.y, cell.x, cell.y) {
+                rows[(position.y + cell.y) as usize][(position.x + cell.x) as usize] = value;
+            }
+        }
+    }
+
+    Ok(MergedGrid { offset, size, rows })
+}
+
+/// Deserializes a level's layers one at a time, so that errors can report which
+/// layer failed to parse.
+fn deserialize_layers<'de, D>(deserializer: D) -> Result<Vec<Layer>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(LayersVisitor)
+}
+
+/// Deserializes a list of layers, adding the index and name of the layer that failed
+/// to any errors.
+struct LayersVisitor;
+
+impl<'de> Visitor<'de> for LayersVisitor {
+    type Value = Vec<Layer>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a list of layers")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<Layer>, A::Error> {
+        let mut layers = Vec::new();
+
+        loop {
+            let mut name = None;
+
+            match seq.next_element_seed(LayerVisitor { name: &mut name }) {
+                Ok(Some(layer)) => layers.push(layer),
+                Ok(None) => return Ok(layers),
+                Err(e) => {
+                    let i = layers.len();
+                    let e = e.to_string();
+                    let e = without_position(&e);
+
+                    return Err(match name {
+                        Some(name) => {
+                            A::Error::custom(format!("invalid layer {} ('{}'): {}", i, name, e))
+                        }
+                        None => A::Error::custom(format!("invalid layer {}: {}", i, e)),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Removes the position that serde_json adds to the end of its error messages.
+///
+/// This is used when wrapping an error, as serde_json will add the position to the new
+/// error too, and it would otherwise be written twice.
+fn without_position(message: &str) -> &str {
+    let is_number =
+        |s: Option<&str>| s.is_some_and(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()));
+
+    if let Some(i) = message.rfind(" at line ") {
+        let mut parts = message[i + " at line ".len()..].split(" column ");
+
+        if is_number(parts.next()) && is_number(parts.next()) && parts.next().is_none() {
+            return &message[..i];
+        }
+    }
+
+    message
+}
+
+fn remove_zero_offsets(object: &mut serde_json::Map<String, serde_json::Value>) {
+    for key in &["offsetX", "offsetY"] {
+        if object.get(*key).and_then(|v| v.as_f64()) == Some(0.0) {
+            object.remove(*key);
+        }
+    }
+}
+
+fn empty_tensor_layer(cells_x: i32, cells_y: i32) -> Vec<Vec<i32>> {
+    vec![vec![-1; cells_x.max(0) as usize]; cells_y.max(0) as usize]
+}
+
+fn set_tensor_cell(cells: &mut [Vec<i32>], position: Vec2<i32>, value: i32) {
+    if position.x < 0 || position.y < 0 {
+        return;
+    }
+
+    if let Some(cell) = cells
+        .get_mut(position.y as usize)
+        .and_then(|row| row.get_mut(position.x as usize))
+    {
+        *cell = value;
+    }
+}
+
+/// An entity instance.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct TileLayer {
-    /// The name of the layer.
+pub struct Entity {
+    /// The entity's name.
     pub name: String,
 
+    /// The entity's ID.
+    pub id: i32,
+
     /// The unique export ID of the entity.
     #[serde(rename = "_eid")]
     pub export_id: String,
 
-    /// The layer's offset on the X axis.
-    pub offset_x: f32,
+    /// The X position of the entity.
+    pub x: f32,
 
-    /// The layer's offset on the Y axis.
-    pub offset_y: f32,
+    /// The Y position of the entity.
+    pub y: f32,
 
-    /// The width of the layer's grid cells.
-    pub grid_cell_width: i32,
+    /// The width of the entity.
+    /// Will only be present if the entity template was defined as resizable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f32>,
 
-    /// The height of the layer's grid cells.
-    pub grid_cell_height: i32,
+    /// The width of the entity.
+    /// Will only be present if the entity template was defined as resizable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
 
-    /// The number of grid cells on the X axis.
-    pub grid_cells_x: i32,
+    /// The X origin of the entity.
+    /// Will only be present if the entity template defined an origin.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_x: Option<f32>,
 
-    /// The number of grid cells on the Y axis.
-    pub grid_cells_y: i32,
+    /// The Y origin of the entity.
+    /// Will only be present if the entity template defined an origin.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_y: Option<f32>,
 
-    /// The name of the tileset used for this layer.
-    pub tileset: String,
+    /// The rotation of the entity.
+    /// Will only be present if the entity template was defined as rotatable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<f32>,
 
-    /// The tile data.
+    /// Whether the entity is flipped on the X axis.
+    /// Will only be present if the entity template was defined as X-flippable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flipped_x: Option<bool>,
+
+    /// Whether the entity is flipped on the Y axis.
+    /// Will only be present if the entity template was defined as Y-flippable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flipped_y: Option<bool>,
+
+    /// The entity's nodes.
+    /// Will only be present if the entity template was defined as having nodes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<Vec<Vec2<f32>>>,
+
+    /// The entity's custom values.
+    /// Will only be present if the entity template was defined as having custom values.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "json::serialize_sorted_opt"
+    )]
+    pub values: Option<ValueMap<String, Value>>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl Entity {
+    /// Sets one of the entity's custom values, returning the previous value if
+    /// there was one.
     ///
-    /// You may want to use the `unpack` method rather than accessing this directly.
-    #[serde(flatten)]
-    pub data: TileLayerStorage,
+    /// If the entity does not have a map of custom values yet, one will be created.
+    pub fn set_value(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        self.values
+            .get_or_insert_with(ValueMap::new)
+            .insert(key.into(), value)
+    }
+
+    /// Removes one of the entity's custom values, returning it if it was present.
+    ///
+    /// If this removes the last value, `values` will be set back to `None`, matching
+    /// how Ogmo omits the field when it is empty.
+    pub fn remove_value(&mut self, key: &str) -> Option<Value> {
+        let values = self.values.as_mut()?;
+        let removed = crate::remove_entry(values, key);
+
+        if values.is_empty() {
+            self.values = None;
+        }
+
+        removed
+    }
+
+    /// Returns an iterator over the entity's custom values, sorted by name.
+    ///
+    /// If the entity has no custom values, nothing will be yielded.
+    pub fn values_sorted(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values.iter().flat_map(sorted_values)
+    }
+
+    /// Gets the entity's axis-aligned bounding box, as a pair of minimum and maximum
+    /// corners.
+    ///
+    /// The entity's position marks its origin, so the box is shifted by the origin. If the
+    /// entity doesn't specify its own size or origin, the values from the template are
+    /// used. Rotation is not taken into account.
+    pub fn aabb(&self, template: &EntityTemplate) -> (Vec2<f32>, Vec2<f32>) {
+        let width = self.width.unwrap_or(template.size.x);
+        let height = self.height.unwrap_or(template.size.y);
+        let origin_x = self.origin_x.unwrap_or(template.origin.x);
+        let origin_y = self.origin_y.unwrap_or(template.origin.y);
+
+        let min = Vec2 {
+            x: self.x - origin_x,
+            y: self.y - origin_y,
+        };
+
+        let max = Vec2 {
+            x: min.x + width,
+            y: min.y + height,
+        };
+
+        (min, max)
+    }
+
+    /// Returns an iterator over the entity's nodes.
+    ///
+    /// Nodes are stored in the same co-ordinate space as the entity's position, so
+    /// subtract `x` and `y` if you need them relative to the entity. If the entity has
+    /// no nodes, nothing will be yielded.
+    pub fn nodes_iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Vec2<f32>> + ExactSizeIterator + '_ {
+        self.node_slice().iter().copied()
+    }
+
+    /// Returns an iterator over each pair of consecutive nodes, as `(from, to)`.
+    ///
+    /// This is useful for entities that use their nodes as a path. The entity's own
+    /// position is not included - chain it onto the front of `nodes_iter` if the path
+    /// should start at the entity.
+    pub fn node_segments(&self) -> impl Iterator<Item = (Vec2<f32>, Vec2<f32>)> + '_ {
+        self.node_slice().windows(2).map(|pair| (pair[0], pair[1]))
+    }
+
+    fn node_slice(&self) -> &[Vec2<f32>] {
+        self.nodes.as_deref().unwrap_or(&[])
+    }
+}
+
+/// A decal instance.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Decal {
+    /// The X position of the decal.
+    ///
+    /// Decals have their origin at the center of the image, so you may need
+    /// to correct for this when rendering.
+    pub x: f32,
+
+    /// The Y position of the decal.
+    ///
+    /// Decals have their origin at the center of the image, so you may need
+    /// to correct for this when rendering.
+    pub y: f32,
+
+    /// The scale of the decal on the X axis.
+    /// Will only be present if the decal template was defined as scalable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_x: Option<f32>,
+
+    /// The scale of the decal on the Y axis.
+    /// Will only be present if the decal template was defined as scalable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_y: Option<f32>,
+
+    /// The rotation of the decal.
+    /// Will only be present if the decal template was defined as rotatable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<f32>,
+
+    /// The name of the decal's texture.
+    pub texture: String,
+
+    /// Custom values associated with the decal.
+    ///
+    /// Levels exported by older versions of Ogmo may not include this field, in which
+    /// case it will be empty.
+    #[serde(
+        default,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub values: ValueMap<String, Value>,
+}
+
+impl Decal {
+    /// Sets one of the decal's custom values, returning the previous value if
+    /// there was one.
+    pub fn set_value(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        self.values.insert(key.into(), value)
+    }
+
+    /// Removes one of the decal's custom values, returning it if it was present.
+    pub fn remove_value(&mut self, key: &str) -> Option<Value> {
+        crate::remove_entry(&mut self.values, key)
+    }
+
+    /// Returns an iterator over the decal's custom values, sorted by name.
+    pub fn values_sorted(&self) -> impl Iterator<Item = (&str, &Value)> {
+        sorted_values(&self.values)
+    }
+}
+
+/// A layer instance.
+///
+/// The type of each layer is determined by the field that its data is stored in (e.g.
+/// `data` or `data2D` for a tile layer). Layers without any recognized data are kept as
+/// `Layer::Unknown`, rather than failing to load.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Layer {
+    /// A tile layer.
+    Tile(TileLayer),
+
+    /// A tile co-ords layer.
+    TileCoords(TileCoordsLayer),
+
+    /// A grid layer.
+    Grid(GridLayer),
+
+    /// An entity layer.
+    Entity(EntityLayer),
+
+    /// A decal layer.
+    Decal(DecalLayer),
+
+    /// A layer of a type that this crate does not recognize, such as one added in a
+    /// newer version of Ogmo.
+    ///
+    /// The raw JSON is kept so that the layer can be written back out. Unless the
+    /// `preserve-order` feature is enabled, its fields will be written in sorted order.
+    Unknown(serde_json::Value),
+}
+
+impl<'de> Deserialize<'de> for Layer {
+    fn deserialize<D>(deserializer: D) -> Result<Layer, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        LayerVisitor { name: &mut None }.deserialize(deserializer)
+    }
+}
+
+/// Deserializes a layer, working out its type from the field that its data is stored in.
+///
+/// The name of the layer is recorded as soon as it has been read, so that it can be
+/// included in any errors from the rest of the layer.
+struct LayerVisitor<'a> {
+    name: &'a mut Option<String>,
+}
+
+impl<'de> DeserializeSeed<'de> for LayerVisitor<'_> {
+    type Value = Layer;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Layer, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LayerVisitor<'_> {
+    type Value = Layer;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a layer")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Layer, A::Error> {
+        // The data is parsed straight into its final type. Everything else is small, so it
+        // gets buffered and then deserialized as normal once the type of layer is known.
+        let mut fields = serde_json::Map::new();
+        let mut data = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            let value = match key.as_str() {
+                "data" => LayerData::Tile(TileLayerStorage::Data(map.next_value()?)),
+                "data2D" => LayerData::Tile(TileLayerStorage::Data2D(map.next_value()?)),
+                "dataCoords" => {
+                    LayerData::TileCoords(TileCoordsLayerStorage::DataCoords(map.next_value()?))
+                }
+                "dataCoords2D" => {
+                    LayerData::TileCoords(TileCoordsLayerStorage::DataCoords2D(map.next_value()?))
+                }
+                "grid" => LayerData::Grid(GridLayerStorage::Grid(map.next_value()?)),
+                "grid2D" => LayerData::Grid(GridLayerStorage::Grid2D(map.next_value()?)),
+                "entities" => LayerData::Entity(map.next_value()?),
+                "decals" => LayerData::Decal(map.next_value()?),
+
+                _ => {
+                    let value: serde_json::Value = map.next_value()?;
+
+                    if key == "name" {
+                        *self.name = value.as_str().map(str::to_owned);
+                    }
+
+                    fields.insert(key, value);
+                    continue;
+                }
+            };
+
+            if let Some((first, _)) = &data {
+                return Err(A::Error::custom(format!(
+                    "layer has both `{}` and `{}`",
+                    first, key
+                )));
+            }
+
+            data = Some((key, value));
+        }
+
+        let (key, data) = match data {
+            Some(data) => data,
+            None => return Ok(Layer::Unknown(serde_json::Value::Object(fields))),
+        };
+
+        // The data has already been parsed, so an empty placeholder is used in its place.
+        fields.insert(key, serde_json::Value::Array(Vec::new()));
+        let fields = serde_json::Value::Object(fields);
+
+        let layer =
+            match data {
+                LayerData::Tile(data) => TileLayer::deserialize(fields)
+                    .map(|layer| Layer::Tile(TileLayer { data, ..layer })),
+                LayerData::TileCoords(data) => TileCoordsLayer::deserialize(fields)
+                    .map(|layer| Layer::TileCoords(TileCoordsLayer { data, ..layer })),
+                LayerData::Grid(data) => GridLayer::deserialize(fields)
+                    .map(|layer| Layer::Grid(GridLayer { data, ..layer })),
+                LayerData::Entity(entities) => EntityLayer::deserialize(fields)
+                    .map(|layer| Layer::Entity(EntityLayer { entities, ..layer })),
+                LayerData::Decal(decals) => DecalLayer::deserialize(fields)
+                    .map(|layer| Layer::Decal(DecalLayer { decals, ..layer })),
+            };
+
+        layer.map_err(A::Error::custom)
+    }
+}
+
+/// The data of a layer, which is parsed before the rest of the layer's fields.
+enum LayerData {
+    Tile(TileLayerStorage),
+    TileCoords(TileCoordsLayerStorage),
+    Grid(GridLayerStorage),
+    Entity(Vec<Entity>),
+    Decal(Vec<Decal>),
+}
+
+impl Layer {
+    /// Gets the name of the layer.
+    pub fn name(&self) -> &str {
+        match self {
+            Layer::Tile(data) => &data.name,
+            Layer::TileCoords(data) => &data.name,
+            Layer::Grid(data) => &data.name,
+            Layer::Entity(data) => &data.name,
+            Layer::Decal(data) => &data.name,
+            Layer::Unknown(data) => raw_str(data, "name"),
+        }
+    }
+
+    /// Gets the export ID of the layer's template.
+    pub fn export_id(&self) -> &str {
+        match self {
+            Layer::Tile(data) => &data.export_id,
+            Layer::TileCoords(data) => &data.export_id,
+            Layer::Grid(data) => &data.export_id,
+            Layer::Entity(data) => &data.export_id,
+            Layer::Decal(data) => &data.export_id,
+            Layer::Unknown(data) => raw_str(data, "_eid"),
+        }
+    }
+
+    /// Gets the layer as a tile layer, if it is one.
+    pub fn as_tile(&self) -> Option<&TileLayer> {
+        match self {
+            Layer::Tile(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a mutable tile layer, if it is one.
+    pub fn as_tile_mut(&mut self) -> Option<&mut TileLayer> {
+        match self {
+            Layer::Tile(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer into a tile layer, if it is one.
+    pub fn into_tile(self) -> Option<TileLayer> {
+        match self {
+            Layer::Tile(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a tile co-ords layer, if it is one.
+    pub fn as_tile_coords(&self) -> Option<&TileCoordsLayer> {
+        match self {
+            Layer::TileCoords(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a mutable tile co-ords layer, if it is one.
+    pub fn as_tile_coords_mut(&mut self) -> Option<&mut TileCoordsLayer> {
+        match self {
+            Layer::TileCoords(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer into a tile co-ords layer, if it is one.
+    pub fn into_tile_coords(self) -> Option<TileCoordsLayer> {
+        match self {
+            Layer::TileCoords(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a grid layer, if it is one.
+    pub fn as_grid(&self) -> Option<&GridLayer> {
+        match self {
+            Layer::Grid(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a mutable grid layer, if it is one.
+    pub fn as_grid_mut(&mut self) -> Option<&mut GridLayer> {
+        match self {
+            Layer::Grid(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer into a grid layer, if it is one.
+    pub fn into_grid(self) -> Option<GridLayer> {
+        match self {
+            Layer::Grid(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as an entity layer, if it is one.
+    pub fn as_entity(&self) -> Option<&EntityLayer> {
+        match self {
+            Layer::Entity(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a mutable entity layer, if it is one.
+    pub fn as_entity_mut(&mut self) -> Option<&mut EntityLayer> {
+        match self {
+            Layer::Entity(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer into an entity layer, if it is one.
+    pub fn into_entity(self) -> Option<EntityLayer> {
+        match self {
+            Layer::Entity(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a decal layer, if it is one.
+    pub fn as_decal(&self) -> Option<&DecalLayer> {
+        match self {
+            Layer::Decal(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer as a mutable decal layer, if it is one.
+    pub fn as_decal_mut(&mut self) -> Option<&mut DecalLayer> {
+        match self {
+            Layer::Decal(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer into a decal layer, if it is one.
+    pub fn into_decal(self) -> Option<DecalLayer> {
+        match self {
+            Layer::Decal(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// Converts an error from a streaming deserializer, separating out failures of the
+/// underlying reader.
+#[cfg(feature = "std")]
+fn stream_error(e: serde_json::Error) -> Error {
+    match e.classify() {
+        serde_json::error::Category::Io => Error::Io(e.into()),
+        _ => Error::Json(e),
+    }
+}
+
+/// Gets a string field from a raw layer, or an empty string if it is not present.
+fn raw_str<'a>(raw: &'a serde_json::Value, key: &str) -> &'a str {
+    raw.get(key)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+}
+
+/// A level whose custom values have been resolved against the project's value
+/// templates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedLevel {
+    /// The untyped level data.
+    ///
+    /// Its custom values are left as they were loaded - edits to the typed values are
+    /// applied when the level is converted back via `to_level` or `to_json`.
+    pub level: Level,
+
+    /// The level's custom values.
+    pub values: ValueMap<String, TypedValue>,
+
+    /// The custom values of the level's entities.
+    ///
+    /// These are keyed by the index of the entity's layer in `level.layers`, and the
+    /// index of the entity within that layer. Entity IDs are not used, as they are not
+    /// guaranteed to be unique (e.g. in levels created by `Level::merged`).
+    pub entity_values: ValueMap<(usize, usize), ValueMap<String, TypedValue>>,
+
+    /// The values as they were originally resolved, used to find which ones have been
+    /// edited.
+    resolved_values: ValueMap<String, TypedValue>,
+    resolved_entity_values: ValueMap<(usize, usize), ValueMap<String, TypedValue>>,
+}
+
+impl TypedLevel {
+    /// Converts the typed level back into an untyped level.
+    ///
+    /// Only the values that have been added, changed or removed since the level was
+    /// resolved are written back, so that unchanged values keep the exact form they
+    /// were loaded in (and defaults that were filled in are not added to the level).
+    pub fn to_level(&self) -> Level {
+        let mut level = self.level.clone();
+
+        for (key, value) in changed_values(&self.values, &self.resolved_values) {
+            match value {
+                Some(value) => level.values.insert(key.to_owned(), value),
+                None => crate::remove_entry(&mut level.values, key),
+            };
+        }
+
+        let layers = level
+            .layers
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, layer)| Some((i, layer.as_entity_mut()?)));
+
+        for (layer_index, layer) in layers {
+            for (entity_index, entity) in layer.entities.iter_mut().enumerate() {
+                let key = (layer_index, entity_index);
+                let empty = ValueMap::new();
+                let values = self.entity_values.get(&key).unwrap_or(&empty);
+                let resolved = self.resolved_entity_values.get(&key).unwrap_or(&empty);
+
+                for (key, value) in changed_values(values, resolved) {
+                    match value {
+                        Some(value) => entity.set_value(key, value),
+                        None => entity.remove_value(key),
+                    };
+                }
+            }
+        }
+
+        level
+    }
+
+    /// Writes the level to a JSON string, including any edits to the typed values.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if serialization fails.
+    pub fn to_json(&self) -> Result<String, Error> {
+        self.to_level().to_json()
+    }
+}
+
+/// Compares a map of typed values against the values it was originally resolved as,
+/// returning the untyped form of each value that was added or changed, or `None` for
+/// each value that was removed.
+fn changed_values<'a>(
+    values: &'a ValueMap<String, TypedValue>,
+    resolved: &'a ValueMap<String, TypedValue>,
+) -> Vec<(&'a str, Option<Value>)> {
+    let mut changed: Vec<_> = values
+        .iter()
+        .filter(|(key, value)| resolved.get(*key) != Some(*value))
+        .map(|(key, value)| (key.as_str(), Some(value.to_value())))
+        .chain(
+            resolved
+                .keys()
+                .filter(|key| !values.contains_key(*key))
+                .map(|key| (key.as_str(), None)),
+        )
+        .collect();
+
+    // Sorted so that values are inserted in the same order between runs.
+    changed.sort_unstable_by_key(|(key, _)| *key);
+    changed
+}
+
+/// A level where the data for every layer has been unpacked up front.
+///
+/// This trades memory usage for speed, in cases where the same layer data needs to be
+/// iterated over multiple times.
+#[derive(Clone, Debug)]
+pub struct UnpackedLevel<'a> {
+    /// The unpacked layers, in the same order as they appear in the level.
+    pub layers: Vec<UnpackedLayer<'a>>,
+}
+
+/// A renderable item from a level, produced by `Level::flatten`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlatItem<'a> {
+    /// A tile from a tile layer.
+    Tile {
+        /// The index of the tile's tileset in the project's `tilesets`.
+        tileset: usize,
+
+        /// The ID of the tile in the tileset.
+        id: i32,
+
+        /// The position of the tile's top left corner, in pixels.
+        position: Vec2<f32>,
+    },
+
+    /// A tile from a tile co-ords layer.
+    TileUv {
+        /// The index of the tile's tileset in the project's `tilesets`.
+        tileset: usize,
+
+        /// The area of the tileset's texture that contains the tile, in pixels.
+        uv: Rect<i32>,
+
+        /// The position of the tile's top left corner, in pixels.
+        position: Vec2<f32>,
+    },
+
+    /// A non-empty cell from a grid layer.
+    Grid {
+        /// The area covered by the cell, in pixels.
+        rect: Rect<f32>,
+
+        /// The value of the cell.
+        value: &'a str,
+    },
+
+    /// An entity.
+    Entity {
+        /// The entity's data.
+        entity: &'a Entity,
+
+        /// The position of the entity's origin, in pixels.
+        position: Vec2<f32>,
+    },
+
+    /// A decal.
+    Decal {
+        /// The path to the decal's texture, relative to the project.
+        texture: String,
+
+        /// The position, rotation and scale of the decal.
+        transform: DecalTransform,
+    },
+}
+
+/// The position, rotation and scale of a decal, produced by `Level::flatten`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DecalTransform {
+    /// The position of the center of the decal, in pixels.
+    pub position: Vec2<f32>,
+
+    /// The rotation of the decal, in radians.
+    pub rotation: f32,
+
+    /// The scale of the decal.
+    pub scale: Vec2<f32>,
+}
+
+/// The unpacked data for a layer.
+#[derive(Clone, Debug)]
+pub enum UnpackedLayer<'a> {
+    /// The tiles from a tile layer.
+    Tile(Vec<Tile>),
+
+    /// The tiles from a tile co-ords layer.
+    TileCoords(Vec<TileCoords>),
+
+    /// The cells from a grid layer.
+    Grid(Vec<GridCell<'a>>),
+
+    /// The entities from an entity layer.
+    Entity(&'a [Entity]),
+
+    /// The decals from a decal layer.
+    Decal(&'a [Decal]),
+
+    /// The raw data from a layer of an unknown type.
+    Unknown(&'a serde_json::Value),
+}
+
+impl<'a> UnpackedLayer<'a> {
+    fn new(layer: &'a Layer) -> UnpackedLayer<'a> {
+        match layer {
+            Layer::Tile(data) => UnpackedLayer::Tile(data.unpack().collect()),
+            Layer::TileCoords(data) => UnpackedLayer::TileCoords(data.unpack().collect()),
+            Layer::Grid(data) => UnpackedLayer::Grid(data.unpack().collect()),
+            Layer::Entity(data) => UnpackedLayer::Entity(&data.entities),
+            Layer::Decal(data) => UnpackedLayer::Decal(&data.decals),
+            Layer::Unknown(data) => UnpackedLayer::Unknown(data),
+        }
+    }
+}
+
+/// A tile layer.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TileLayer {
+    /// The name of the layer.
+    pub name: String,
+
+    /// The unique export ID of the entity.
+    #[serde(rename = "_eid")]
+    pub export_id: String,
+
+    /// The layer's offset on the X axis.
+    #[serde(default)]
+    pub offset_x: f32,
+
+    /// The layer's offset on the Y axis.
+    #[serde(default)]
+    pub offset_y: f32,
+
+    /// The width of the layer's grid cells.
+    pub grid_cell_width: i32,
+
+    /// The height of the layer's grid cells.
+    pub grid_cell_height: i32,
+
+    /// The number of grid cells on the X axis.
+    pub grid_cells_x: i32,
+
+    /// The number of grid cells on the Y axis.
+    pub grid_cells_y: i32,
+
+    /// The name of the tileset used for this layer.
+    ///
+    /// If the layer does not have a tileset assigned, this will be empty.
+    #[serde(default, deserialize_with = "deserialize_tileset")]
+    pub tileset: String,
+
+    /// The tile data.
+    ///
+    /// You may want to use the `unpack` method rather than accessing this directly.
+    #[serde(flatten)]
+    pub data: TileLayerStorage,
+}
+
+impl TileLayer {
+    /// Unpack the tile data from the layer.
+    ///
+    /// If the data is stored in 1D and `grid_cells_x` is zero, no tiles will be yielded.
+    pub fn unpack(&self) -> TileIter<'_> {
+        let cells = match &self.data {
+            TileLayerStorage::Data(data) => Cells::flat(data, self.grid_cells_x),
+            TileLayerStorage::Data2D(data) => Cells::rows(data),
+        };
+
+        TileIter {
+            cells,
+            cell_size: self.cell_size(),
+        }
+    }
+
+    /// Unpack the tile data from the layer, consuming it.
+    ///
+    /// This yields the same tiles as `unpack`, but the returned iterator does not
+    /// borrow the layer, so it can be stored or sent to another thread.
+    pub fn into_unpack(self) -> impl ExactSizeIterator<Item = Tile> {
+        let cell_size = self.cell_size();
+        let cells_x = self.grid_cells_x;
+
+        match self.data {
+            TileLayerStorage::Data(data) => {
+                let data = if cells_x > 0 { data } else { Vec::new() };
+
+                Either::Left(data.into_iter().enumerate().map(move |(i, v)| {
+                    let grid_x = i as i32 % cells_x;
+                    let grid_y = i as i32 / cells_x;
+
+                    unpack_tile(cell_size, grid_x, grid_y, v)
+                }))
+            }
+
+            TileLayerStorage::Data2D(data) => {
+                let len = data.iter().map(Vec::len).sum();
+
+                Either::Right(ExactLen::new(
+                    len,
+                    data.into_iter().enumerate().flat_map(move |(y, row)| {
+                        row.into_iter()
+                            .enumerate()
+                            .map(move |(x, v)| unpack_tile(cell_size, x as i32, y as i32, v))
+                    }),
+                ))
+            }
+        }
+    }
+
+    /// Unpack the tile data from the layer in parallel.
+    ///
+    /// This yields the same tiles as `unpack`, and preserves their order if the
+    /// iterator is collected. It is only available if the `rayon` feature is enabled.
+    #[cfg(feature = "rayon")]
+    pub fn par_unpack(&self) -> impl ParallelIterator<Item = Tile> + '_ {
+        let cell_size = self.cell_size();
+
+        match &self.data {
+            TileLayerStorage::Data(data) => {
+                let data: &[i32] = if self.grid_cells_x > 0 { data } else { &[] };
+
+                Either::Left(data.par_iter().enumerate().map(move |(i, &v)| {
+                    let grid_x = i as i32 % self.grid_cells_x;
+                    let grid_y = i as i32 / self.grid_cells_x;
+
+                    unpack_tile(cell_size, grid_x, grid_y, v)
+                }))
+            }
+
+            TileLayerStorage::Data2D(data) => {
+                Either::Right(data.par_iter().enumerate().flat_map_iter(move |(y, row)| {
+                    row.iter()
+                        .enumerate()
+                        .map(move |(x, &v)| unpack_tile(cell_size, x as i32, y as i32, v))
+                }))
+            }
+        }
+    }
+
+    /// Parses a tile layer from a reader, passing each tile to a callback as it is
+    /// parsed rather than storing it in the layer.
+    ///
+    /// The callback is given the tile's ID (with `-1` meaning 'empty'), followed by
+    /// its X and Y position in the grid. The returned layer has every field filled
+    /// in apart from `data`, which will be empty (but will still reflect whether
+    /// the layer was stored in 1D or 2D).
+    ///
+    /// This is useful for very large layers, as the tile data never has to be held in
+    /// memory all at once. It is only available if the `std` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Io` will be returned if the reader fails.
+    /// * `Error::Json` will be returned if deserialization fails. This includes the
+    ///   case where the data is stored in 1D and `gridCellsX` is not positive, or comes
+    ///   after the data (the Ogmo editor always writes it first).
+    #[cfg(feature = "std")]
+    pub fn stream_data<R, F>(reader: R, f: F) -> Result<TileLayer, Error>
+    where
+        R: io::Read,
+        F: FnMut(i32, i32, i32),
+    {
+        let mut de = serde_json::Deserializer::from_reader(reader);
+
+        let layer = de
+            .deserialize_map(TileLayerStream { f })
+            .map_err(stream_error)?;
+
+        de.end().map_err(stream_error)?;
+
+        Ok(layer)
+    }
+
+    fn cell_size(&self) -> Vec2<i32> {
+        Vec2 {
+            x: self.grid_cell_width,
+            y: self.grid_cell_height,
+        }
+    }
+
+    /// Unpack the tile data from the layer, with the layer's offset applied to
+    /// the pixel positions.
+    ///
+    /// The offset is truncated to a whole number of pixels.
+    pub fn unpack_with_offset(&self) -> impl ExactSizeIterator<Item = Tile> + '_ {
+        self.unpack_with_world_offset(Vec2 { x: 0.0, y: 0.0 })
+    }
+
+    /// Unpack the tile data from the layer, with both the layer's offset and the
+    /// given world offset applied to the pixel positions.
+    ///
+    /// Passing in `Level::world_offset` gives the absolute position of each tile, which
+    /// is useful when stitching together chunked levels. The combined offset is
+    /// truncated to a whole number of pixels.
+    pub fn unpack_with_world_offset(
+        &self,
+        world_offset: Vec2<f32>,
+    ) -> impl ExactSizeIterator<Item = Tile> + '_ {
+        let offset_x = (self.offset_x + world_offset.x) as i32;
+        let offset_y = (self.offset_y + world_offset.y) as i32;
+
+        self.unpack().map(move |mut tile| {
+            tile.pixel_position.x += offset_x;
+            tile.pixel_position.y += offset_y;
+            tile
+        })
+    }
+
+    /// Gets the tile at the given grid position, without unpacking the whole layer.
+    ///
+    /// Returns `None` if the position is out of bounds, or if its pixel position does
+    /// not fit in an `i32`.
+    pub fn get(&self, grid_x: i32, grid_y: i32) -> Option<Tile> {
+        if !in_grid(self.grid_cells_x, self.grid_cells_y, grid_x, grid_y) {
+            return None;
+        }
+
+        let v = match &self.data {
+            TileLayerStorage::Data(data) => {
+                *data.get(flat_index(self.grid_cells_x, grid_x, grid_y)?)?
+            }
+            TileLayerStorage::Data2D(data) => *data.get(grid_y as usize)?.get(grid_x as usize)?,
+        };
+
+        let id = if v == -1 { None } else { Some(v) };
+
+        let cell_size = Vec2 {
+            x: self.grid_cell_width,
+            y: self.grid_cell_height,
+        };
+
+        Some(Tile {
+            id,
+            grid_position: Vec2 {
+                x: grid_x,
+                y: grid_y,
+            },
+            pixel_position: pixel_position(cell_size, grid_x, grid_y)?,
+        })
+    }
+
+    /// Checks that the layer's data matches its declared grid size.
+    ///
+    /// For 1D data, the number of tiles must be equal to `grid_cells_x * grid_cells_y`.
+    /// For 2D data, there must be `grid_cells_y` rows, each containing `grid_cells_x`
+    /// tiles. If this is not the case, `unpack` will produce incorrect positions.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if the data does not match the grid size.
+    pub fn check_dimensions(&self) -> Result<(), Error> {
+        match self.dimension_mismatch() {
+            Some(message) => Err(Error::Inconsistent(format!(
+                "tile layer '{}' does not match its grid size: {}",
+                self.name, message
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Describes how the layer's data differs from its grid size, if it does.
+    fn dimension_mismatch(&self) -> Option<String> {
+        let cells_x = self.grid_cells_x.max(0) as usize;
+        let cells_y = self.grid_cells_y.max(0) as usize;
+
+        match &self.data {
+            TileLayerStorage::Data(data) => {
+                if data.len() != cells_x * cells_y {
+                    return Some(format!(
+                        "expected {} tiles, found {}",
+                        cells_x * cells_y,
+                        data.len()
+                    ));
+                }
+            }
+
+            TileLayerStorage::Data2D(data) => {
+                if data.len() != cells_y {
+                    return Some(format!("expected {} rows, found {}", cells_y, data.len()));
+                }
+
+                for (y, row) in data.iter().enumerate() {
+                    if row.len() != cells_x {
+                        return Some(format!(
+                            "expected {} tiles in row {}, found {}",
+                            cells_x,
+                            y,
+                            row.len()
+                        ));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Creates a view of the layer, which allows tiles to be looked up by grid position
+    /// without needing to handle the different storage modes.
+    pub fn view(&self) -> TileView<'_> {
+        TileView { layer: self }
+    }
+
+    /// Counts the number of non-empty tiles in the layer.
+    ///
+    /// This scans the raw data directly, so it is cheaper than unpacking the layer.
+    pub fn filled_count(&self) -> usize {
+        match &self.data {
+            TileLayerStorage::Data(data) => data.iter().filter(|&&v| v != -1).count(),
+            TileLayerStorage::Data2D(data) => data.iter().flatten().filter(|&&v| v != -1).count(),
+        }
+    }
+
+    /// Gets the IDs of the eight tiles surrounding the given grid position, for use
+    /// in autotiling.
+    ///
+    /// The neighbors are returned in clockwise order, starting from the tile above:
+    /// north, north-east, east, south-east, south, south-west, west and north-west.
+    /// Positive Y is down, so north is `grid_y - 1`. Tiles that are empty or out of
+    /// bounds are returned as `None`.
+    pub fn neighbors(&self, grid_x: i32, grid_y: i32) -> [Option<i32>; 8] {
+        const OFFSETS: [(i32, i32); 8] = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+
+        OFFSETS.map(|(x, y)| self.get(grid_x + x, grid_y + y).and_then(|tile| tile.id))
+    }
+
+    /// Removes any fully empty rows and columns from the edges of the layer.
+    ///
+    /// The layer's offset is adjusted so that the remaining tiles stay in the same
+    /// position in the world. If the layer is completely empty, it will be cropped
+    /// down to zero cells.
+    ///
+    /// Returns the number of cells that were removed from the left and top edges,
+    /// and the new size of the layer in cells.
+    pub fn autocrop(&mut self) -> (Vec2<i32>, Vec2<i32>) {
+        let (min, size) = filled_bounds(
+            self.unpack()
+                .filter(|tile| tile.id.is_some())
+                .map(|tile| tile.grid_position),
+        );
+
+        *self = self.subregion(min, size);
+
+        (min, size)
+    }
+
+    /// Sets the tile at the given grid position, using `None` to represent an empty tile.
+    ///
+    /// Returns `false` if the position is out of bounds, in which case the layer
+    /// will not be modified.
+    pub fn set(&mut self, grid_x: i32, grid_y: i32, id: Option<i32>) -> bool {
+        if !in_grid(self.grid_cells_x, self.grid_cells_y, grid_x, grid_y) {
+            return false;
+        }
+
+        let cell = match &mut self.data {
+            TileLayerStorage::Data(data) => {
+                flat_index(self.grid_cells_x, grid_x, grid_y).and_then(move |i| data.get_mut(i))
+            }
+            TileLayerStorage::Data2D(data) => data
+                .get_mut(grid_y as usize)
+                .and_then(|row| row.get_mut(grid_x as usize)),
+        };
+
+        match cell {
+            Some(cell) => {
+                *cell = id.unwrap_or(-1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Creates a new layer containing a rectangular region of this layer's cells.
+    ///
+    /// `min` is the top left corner of the region and `size` is its size, both in
+    /// grid co-ordinates. The new layer's offset is adjusted so that its tiles stay in
+    /// the same position in the world, and it will use the same storage mode as this
+    /// layer. Any part of the region that falls outside of this layer will be empty.
+    pub fn subregion(&self, min: Vec2<i32>, size: Vec2<i32>) -> TileLayer {
+        let size = Vec2 {
+            x: size.x.max(0),
+            y: size.y.max(0),
+        };
+
+        let rows: Vec<Vec<i32>> = (min.y..min.y + size.y)
+            .map(|y| {
+                (min.x..min.x + size.x)
+                    .map(|x| self.get(x, y).and_then(|tile| tile.id).unwrap_or(-1))
+                    .collect()
+            })
+            .collect();
+
+        let data = match self.data {
+            TileLayerStorage::Data(_) => TileLayerStorage::Data(rows.concat()),
+            TileLayerStorage::Data2D(_) => TileLayerStorage::Data2D(rows),
+        };
+
+        TileLayer {
+            name: self.name.clone(),
+            export_id: self.export_id.clone(),
+            offset_x: self.offset_x + (min.x * self.grid_cell_width) as f32,
+            offset_y: self.offset_y + (min.y * self.grid_cell_height) as f32,
+            grid_cell_width: self.grid_cell_width,
+            grid_cell_height: self.grid_cell_height,
+            grid_cells_x: size.x,
+            grid_cells_y: size.y,
+            tileset: self.tileset.clone(),
+            data,
+        }
+    }
+
+    /// Gets the name of the tileset used for this layer.
+    ///
+    /// Returns `None` if the layer does not have a tileset assigned.
+    pub fn tileset_name(&self) -> Option<&str> {
+        if self.tileset.is_empty() {
+            None
+        } else {
+            Some(&self.tileset)
+        }
+    }
+
+    /// Checks whether two layers have the same dimensions and tiles, regardless of how
+    /// their data is stored.
+    ///
+    /// Other fields (such as the name, offset and tileset) are not compared.
+    pub fn content_eq(&self, other: &TileLayer) -> bool {
+        self.grid_cells_x == other.grid_cells_x
+            && self.grid_cells_y == other.grid_cells_y
+            && grid_positions(self.grid_cells_x, self.grid_cells_y).all(|(x, y)| {
+                self.get(x, y).and_then(|t| t.id) == other.get(x, y).and_then(|t| t.id)
+            })
+    }
+
+    /// Gets the fraction of the layer's cells that contain a tile, from `0.0` to `1.0`.
+    ///
+    /// If the layer has no cells, this will return `0.0`.
+    pub fn coverage(&self) -> f32 {
+        let cells = self.grid_cells_x * self.grid_cells_y;
+
+        if cells <= 0 {
+            return 0.0;
+        }
+
+        let filled = self.unpack().filter(|t| t.id.is_some()).count();
+
+        filled as f32 / cells as f32
+    }
+}
+
+/// Creates a tile from the raw value stored at the given grid position.
+fn unpack_tile(cell_size: Vec2<i32>, grid_x: i32, grid_y: i32, v: i32) -> Tile {
+    let id = if v == -1 { None } else { Some(v) };
+
+    Tile {
+        id,
+        grid_position: Vec2 {
+            x: grid_x,
+            y: grid_y,
+        },
+        pixel_position: Vec2 {
+            x: grid_x * cell_size.x,
+            y: grid_y * cell_size.y,
+        },
+    }
+}
+
+/// Deserializes a layer's tileset name, treating `null` as an empty string.
+fn deserialize_tileset<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Deserializes a tile layer, passing its tiles to a callback instead of storing them.
+#[cfg(feature = "std")]
+struct TileLayerStream<F> {
+    f: F,
+}
+
+#[cfg(feature = "std")]
+impl<'de, F: FnMut(i32, i32, i32)> Visitor<'de> for TileLayerStream<F> {
+    type Value = TileLayer;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a tile layer")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<TileLayer, A::Error> {
+        // Everything apart from the data is small, so it gets buffered and then
+        // deserialized as normal once the data has been streamed.
+        let mut fields = serde_json::Map::new();
+        let mut cells_x = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "data" => {
+                    let width = cells_x.filter(|&x| x > 0).ok_or_else(|| {
+                        A::Error::custom("`gridCellsX` must be positive and come before `data`")
+                    })?;
+
+                    map.next_value_seed(TileIdStream {
+                        f: &mut self.f,
+                        width,
+                        row: 0,
+                    })?;
+                }
+
+                "data2D" => map.next_value_seed(TileRowStream { f: &mut self.f })?,
+
+                _ => {
+                    let value: serde_json::Value = map.next_value()?;
+
+                    if key == "gridCellsX" {
+                        cells_x = value.as_i64().map(|x| x as i32);
+                    }
+
+                    fields.insert(key, value);
+                    continue;
+                }
+            }
+
+            fields.insert(key, serde_json::Value::Array(Vec::new()));
+        }
+
+        TileLayer::deserialize(serde_json::Value::Object(fields)).map_err(A::Error::custom)
+    }
+}
+
+/// Streams an array of tile IDs to a callback, wrapping onto a new row every
+/// `width` tiles.
+#[cfg(feature = "std")]
+struct TileIdStream<'a, F> {
+    f: &'a mut F,
+    width: i32,
+    row: i32,
+}
+
+#[cfg(feature = "std")]
+impl<'de, F: FnMut(i32, i32, i32)> DeserializeSeed<'de> for TileIdStream<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, F: FnMut(i32, i32, i32)> Visitor<'de> for TileIdStream<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("an array of tile IDs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut i = 0;
+
+        while let Some(id) = seq.next_element::<i32>()? {
+            (self.f)(id, i % self.width, self.row + i / self.width);
+            i += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Streams a 2D array of tile IDs to a callback, one row at a time.
+#[cfg(feature = "std")]
+struct TileRowStream<'a, F> {
+    f: &'a mut F,
+}
+
+#[cfg(feature = "std")]
+impl<'de, F: FnMut(i32, i32, i32)> DeserializeSeed<'de> for TileRowStream<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, F: FnMut(i32, i32, i32)> Visitor<'de> for TileRowStream<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a 2D array of tile IDs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut row = 0;
+
+        while seq
+            .next_element_seed(TileIdStream {
+                f: &mut *self.f,
+                width: i32::MAX,
+                row,
+            })?
+            .is_some()
+        {
+            row += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether a grid position falls within a layer's bounds.
+fn in_grid(cells_x: i32, cells_y: i32, grid_x: i32, grid_y: i32) -> bool {
+    grid_x >= 0 && grid_y >= 0 && grid_x < cells_x && grid_y < cells_y
+}
+
+/// Gets the index of a grid position in a layer's flat data.
+///
+/// The position must already have been checked with `in_grid`. A layer's dimensions
+/// are not checked when it is loaded, so this returns `None` rather than overflowing
+/// if they are too large.
+fn flat_index(cells_x: i32, grid_x: i32, grid_y: i32) -> Option<usize> {
+    (grid_y as usize)
+        .checked_mul(cells_x as usize)?
+        .checked_add(grid_x as usize)
+}
+
+/// Gets the pixel position of a grid position, or `None` if it does not fit in an `i32`.
+fn pixel_position(cell_size: Vec2<i32>, grid_x: i32, grid_y: i32) -> Option<Vec2<i32>> {
+    Some(Vec2 {
+        x: grid_x.checked_mul(cell_size.x)?,
+        y: grid_y.checked_mul(cell_size.y)?,
+    })
+}
+
+/// Finds the smallest rectangle of cells containing all of the given positions,
+/// returning its top left corner and size.
+fn filled_bounds(positions: impl Iterator<Item = Vec2<i32>>) -> (Vec2<i32>, Vec2<i32>) {
+    let mut bounds: Option<(Vec2<i32>, Vec2<i32>)> = None;
+
+    for pos in positions {
+        let (min, max) = bounds.get_or_insert((pos, pos));
+
+        min.x = min.x.min(pos.x);
+        min.y = min.y.min(pos.y);
+        max.x = max.x.max(pos.x);
+        max.y = max.y.max(pos.y);
+    }
+
+    match bounds {
+        Some((min, max)) => (
+            min,
+            Vec2 {
+                x: max.x - min.x + 1,
+                y: max.y - min.y + 1,
+            },
+        ),
+        None => (Vec2 { x: 0, y: 0 }, Vec2 { x: 0, y: 0 }),
+    }
+}
+
+/// Returns an iterator over a map of custom values, sorted by name.
+fn sorted_values(values: &ValueMap<String, Value>) -> impl Iterator<Item = (&str, &Value)> {
+    let mut sorted: Vec<_> = values.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    sorted.sort_unstable_by_key(|(k, _)| *k);
+    sorted.into_iter()
+}
+
+/// Returns an iterator over every position in a grid, in row-major order.
+fn grid_positions(cells_x: i32, cells_y: i32) -> impl Iterator<Item = (i32, i32)> {
+    (0..cells_y).flat_map(move |y| (0..cells_x).map(move |x| (x, y)))
+}
+
+/// Joins rows of layer data into a flat list, padding or truncating each row to
+/// `cells_x` cells.
+///
+/// The editor doesn't always write rows of the same length, so this is needed to
+/// keep cells in the right position when flattening.
+fn flatten_rows<T: Clone>(rows: &[Vec<T>], cells_x: usize, empty: T) -> Vec<T> {
+    rows.iter()
+        .flat_map(|row| {
+            row.iter()
+                .cloned()
+                .chain(core::iter::repeat(empty.clone()))
+                .take(cells_x)
+        })
+        .collect()
+}
+
+/// Splits flat layer data into rows of `cells_x` cells.
+///
+/// If `cells_x` is zero, there are no cells that the data could be placed in, so no
+/// rows are returned.
+fn to_rows<T: Clone>(data: &[T], cells_x: usize) -> Vec<Vec<T>> {
+    if cells_x == 0 {
+        return Vec::new();
+    }
+
+    data.chunks(cells_x).map(<[T]>::to_vec).collect()
+}
+
+/// Iterates over the cells of a layer's raw data, along with their grid positions.
+///
+/// This is shared between the named iterators returned by the `unpack` methods, so that
+/// the 1D and 2D storage modes can be handled without boxing.
+#[derive(Clone, Debug)]
+enum Cells<'a, T> {
+    Flat {
+        iter: Enumerate<slice::Iter<'a, T>>,
+        cells_x: i32,
+    },
+    Rows {
+        rows: Enumerate<slice::Iter<'a, Vec<T>>>,
+        row: Option<(i32, Enumerate<slice::Iter<'a, T>>)>,
+        remaining: usize,
+    },
+}
+
+impl<'a, T> Cells<'a, T> {
+    fn flat(data: &'a [T], cells_x: i32) -> Cells<'a, T> {
+        // A layer with no columns can't be indexed into, so treat it as empty
+        // rather than dividing by zero.
+        let data: &[T] = if cells_x > 0 { data } else { &[] };
+
+        Cells::Flat {
+            iter: data.iter().enumerate(),
+            cells_x,
+        }
+    }
+
+    fn rows(data: &'a [Vec<T>]) -> Cells<'a, T> {
+        Cells::Rows {
+            rows: data.iter().enumerate(),
+            row: None,
+            remaining: data.iter().map(Vec::len).sum(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Cells<'a, T> {
+    type Item = (Vec2<i32>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Cells::Flat { iter, cells_x } => {
+                let (i, value) = iter.next()?;
+
+                let position = Vec2 {
+                    x: i as i32 % *cells_x,
+                    y: i as i32 / *cells_x,
+                };
+
+                Some((position, value))
+            }
+
+            Cells::Rows {
+                rows,
+                row,
+                remaining,
+            } => loop {
+                if let Some((y, cells)) = row {
+                    if let Some((x, value)) = cells.next() {
+                        *remaining -= 1;
+                        return Some((Vec2 { x: x as i32, y: *y }, value));
+                    }
+                }
+
+                let (y, next_row) = rows.next()?;
+                *row = Some((y as i32, next_row.iter().enumerate()));
+            },
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Cells::Flat { iter, .. } => iter.size_hint(),
+            Cells::Rows { remaining, .. } => (*remaining, Some(*remaining)),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Cells<'_, T> {}
+
+/// An iterator over the tiles in a `TileLayer`.
+///
+/// This is created by `TileLayer::unpack`, or by iterating over a `&TileLayer`.
+#[derive(Clone, Debug)]
+pub struct TileIter<'a> {
+    cells: Cells<'a, i32>,
+    cell_size: Vec2<i32>,
+}
+
+impl Iterator for TileIter<'_> {
+    type Item = Tile;
+
+    fn next(&mut self) -> Option<Tile> {
+        let (position, &v) = self.cells.next()?;
+        Some(unpack_tile(self.cell_size, position.x, position.y, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl ExactSizeIterator for TileIter<'_> {}
+
+impl<'a> IntoIterator for &'a TileLayer {
+    type Item = Tile;
+    type IntoIter = TileIter<'a>;
+
+    fn into_iter(self) -> TileIter<'a> {
+        self.unpack()
+    }
+}
+
+/// An iterator over the tiles in a `TileCoordsLayer`.
+///
+/// This is created by `TileCoordsLayer::unpack`, or by iterating over a `&TileCoordsLayer`.
+#[derive(Clone, Debug)]
+pub struct TileCoordsIter<'a> {
+    cells: Cells<'a, Vec<i32>>,
+    cell_size: Vec2<i32>,
+}
+
+impl Iterator for TileCoordsIter<'_> {
+    type Item = TileCoords;
+
+    fn next(&mut self) -> Option<TileCoords> {
+        let (position, coords) = self.cells.next()?;
+        Some(unpack_tile_coords(
+            self.cell_size,
+            position.x,
+            position.y,
+            coords,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl ExactSizeIterator for TileCoordsIter<'_> {}
+
+impl<'a> IntoIterator for &'a TileCoordsLayer {
+    type Item = TileCoords;
+    type IntoIter = TileCoordsIter<'a>;
+
+    fn into_iter(self) -> TileCoordsIter<'a> {
+        self.unpack()
+    }
+}
+
+/// An iterator over the cells in a `GridLayer`.
+///
+/// This is created by `GridLayer::unpack`, or by iterating over a `&GridLayer`.
+#[derive(Clone, Debug)]
+pub struct GridCellIter<'a> {
+    cells: Cells<'a, String>,
+    cell_size: Vec2<i32>,
+}
+
+impl<'a> Iterator for GridCellIter<'a> {
+    type Item = GridCell<'a>;
+
+    fn next(&mut self) -> Option<GridCell<'a>> {
+        let (position, value) = self.cells.next()?;
+
+        Some(GridCell {
+            value,
+            grid_position: position,
+            pixel_position: Vec2 {
+                x: position.x * self.cell_size.x,
+                y: position.y * self.cell_size.y,
+            },
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl ExactSizeIterator for GridCellIter<'_> {}
+
+impl<'a> IntoIterator for &'a GridLayer {
+    type Item = GridCell<'a>;
+    type IntoIter = GridCellIter<'a>;
+
+    fn into_iter(self) -> GridCellIter<'a> {
+        self.unpack()
+    }
+}
+
+/// A borrowed view of a tile layer, which can be indexed by grid position.
+///
+/// This hides the difference between the 1D and 2D storage modes, and is cheap to
+/// create and pass around.
+#[derive(Copy, Clone, Debug)]
+pub struct TileView<'a> {
+    /// The layer.
+    pub layer: &'a TileLayer,
+}
+
+impl<'a> TileView<'a> {
+    /// Gets the ID of the tile at the given grid position.
+    ///
+    /// Returns `None` if the tile is empty, or if the position is out of bounds.
+    pub fn get(&self, grid_x: i32, grid_y: i32) -> Option<i32> {
+        self.layer.get(grid_x, grid_y)?.id
+    }
+
+    /// Returns whether the given grid position is within the bounds of the layer.
+    pub fn contains(&self, grid_x: i32, grid_y: i32) -> bool {
+        self.layer.get(grid_x, grid_y).is_some()
+    }
+
+    /// Returns the size of the layer, in grid cells.
+    pub fn dimensions(&self) -> Vec2<i32> {
+        Vec2 {
+            x: self.layer.grid_cells_x,
+            y: self.layer.grid_cells_y,
+        }
+    }
+
+    /// Returns an iterator over the tiles in the layer.
+    pub fn iter(&self) -> TileIter<'a> {
+        self.layer.unpack()
+    }
 }
 
-impl TileLayer {
-    /// Unpack the tile data from the layer.
-    pub fn unpack(&self) -> impl Iterator<Item = Tile> + '_ {
-        match &self.data {
-            TileLayerStorage::Data(data) => {
-                Either::Left(data.iter().enumerate().map(move |(i, &v)| {
-                    let grid_x = i as i32 % self.grid_cells_x;
-                    let grid_y = i as i32 / self.grid_cells_x;
+/// A borrowed view of a grid layer, which can be indexed by grid position.
+///
+/// This hides the difference between the 1D and 2D storage modes, and is cheap to
+/// create and pass around.
+#[derive(Copy, Clone, Debug)]
+pub struct GridView<'a> {
+    /// The layer.
+    pub layer: &'a GridLayer,
+}
 
-                    let pixel_x = grid_x * self.grid_cell_width;
-                    let pixel_y = grid_y * self.grid_cell_height;
+impl<'a> GridView<'a> {
+    /// Gets the value of the cell at the given grid position.
+    ///
+    /// Returns `None` if the position is out of bounds.
+    pub fn get(&self, grid_x: i32, grid_y: i32) -> Option<&'a str> {
+        self.layer.get(grid_x, grid_y).map(|cell| cell.value)
+    }
 
-                    let id = if v == -1 { None } else { Some(v) };
+    /// Returns the size of the layer, in grid cells.
+    pub fn dimensions(&self) -> Vec2<i32> {
+        Vec2 {
+            x: self.layer.grid_cells_x,
+            y: self.layer.grid_cells_y,
+        }
+    }
 
-                    Tile {
-                        id,
-                        grid_position: Vec2 {
-                            x: grid_x,
-                            y: grid_y,
-                        },
-                        pixel_position: Vec2 {
-                            x: pixel_x,
-                            y: pixel_y,
-                        },
-                    }
-                }))
-            }
+    /// Returns an iterator over the cells in the layer.
+    pub fn iter(&self) -> GridCellIter<'a> {
+        self.layer.unpack()
+    }
+}
 
-            TileLayerStorage::Data2D(data) => {
-                Either::Right(data.iter().enumerate().flat_map(move |(y, row)| {
-                    row.iter().enumerate().map(move |(x, &v)| {
-                        let grid_x = x as i32;
-                        let grid_y = y as i32;
+/// Wraps an iterator whose length is known up front, so that it can report an exact
+/// size.
+///
+/// This is used when flattening 2D layer data, as `flat_map` can't know how many
+/// items the remaining rows will yield.
+struct ExactLen<I> {
+    iter: I,
+    remaining: usize,
+}
 
-                        let pixel_x = grid_x * self.grid_cell_width;
-                        let pixel_y = grid_y * self.grid_cell_height;
+impl<I> ExactLen<I> {
+    fn new(len: usize, iter: I) -> ExactLen<I> {
+        ExactLen {
+            iter,
+            remaining: len,
+        }
+    }
+}
 
-                        let id = if v == -1 { None } else { Some(v) };
+impl<I: Iterator> Iterator for ExactLen<I> {
+    type Item = I::Item;
 
-                        Tile {
-                            id,
-                            grid_position: Vec2 {
-                                x: grid_x,
-                                y: grid_y,
-                            },
-                            pixel_position: Vec2 {
-                                x: pixel_x,
-                                y: pixel_y,
-                            },
-                        }
-                    })
-                }))
-            }
-        }
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.iter.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<I: Iterator> ExactSizeIterator for ExactLen<I> {}
+
 /// An individual tile, unpacked from a `TileLayer`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Tile {
     /// The ID of the tile in the tileset.
     ///
@@ -348,7 +3286,7 @@ pub struct Tile {
 }
 
 /// Tile data from a `TileLayer`.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub enum TileLayerStorage {
     /// A flat list of tile IDs.
     ///
@@ -369,6 +3307,34 @@ pub enum TileLayerStorage {
     Data2D(Vec<Vec<i32>>),
 }
 
+impl TileLayerStorage {
+    /// Converts the data into a flat list, if it is not already stored that way.
+    ///
+    /// `cells_x` is the number of cells in each row - this will usually be
+    /// the `grid_cells_x` of the layer that the data belongs to. Each row is padded
+    /// with empty tiles or truncated to this length, so that ragged rows don't
+    /// shift the cells that come after them.
+    pub fn to_1d(&self, cells_x: usize) -> TileLayerStorage {
+        match self {
+            TileLayerStorage::Data(data) => TileLayerStorage::Data(data.clone()),
+            TileLayerStorage::Data2D(data) => {
+                TileLayerStorage::Data(flatten_rows(data, cells_x, -1))
+            }
+        }
+    }
+
+    /// Converts the data into a 2D list, if it is not already stored that way.
+    ///
+    /// `cells_x` is the number of cells in each row - this will usually be
+    /// the `grid_cells_x` of the layer that the data belongs to.
+    pub fn to_2d(&self, cells_x: usize) -> TileLayerStorage {
+        match self {
+            TileLayerStorage::Data(data) => TileLayerStorage::Data2D(to_rows(data, cells_x)),
+            TileLayerStorage::Data2D(data) => TileLayerStorage::Data2D(data.clone()),
+        }
+    }
+}
+
 impl Serialize for TileLayerStorage {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -394,7 +3360,7 @@ impl Serialize for TileLayerStorage {
 }
 
 /// A tile co-ords layer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TileCoordsLayer {
     /// The name of the layer.
@@ -405,9 +3371,11 @@ pub struct TileCoordsLayer {
     pub export_id: String,
 
     /// The layer's offset on the X axis.
+    #[serde(default)]
     pub offset_x: f32,
 
     /// The layer's offset on the Y axis.
+    #[serde(default)]
     pub offset_y: f32,
 
     /// The width of the layer's grid cells.
@@ -423,6 +3391,9 @@ pub struct TileCoordsLayer {
     pub grid_cells_y: i32,
 
     /// The name of the tileset used for this layer.
+    ///
+    /// If the layer does not have a tileset assigned, this will be empty.
+    #[serde(default, deserialize_with = "deserialize_tileset")]
     pub tileset: String,
 
     /// The tile data.
@@ -434,103 +3405,224 @@ pub struct TileCoordsLayer {
 
 impl TileCoordsLayer {
     /// Unpack the tile data from the layer.
-    pub fn unpack(&self) -> impl Iterator<Item = TileCoords> + '_ {
-        match &self.data {
-            TileCoordsLayerStorage::DataCoords(data) => {
-                Either::Left(data.iter().enumerate().map(move |(i, coords)| {
-                    let grid_x = i as i32 % self.grid_cells_x;
-                    let grid_y = i as i32 / self.grid_cells_x;
-
-                    let pixel_x = grid_x * self.grid_cell_width;
-                    let pixel_y = grid_y * self.grid_cell_height;
+    ///
+    /// If the data is stored in 1D and `grid_cells_x` is zero, no tiles will be yielded.
+    ///
+    /// Malformed co-ordinates (anything other than `[-1]`, `[-1, -1]` or a pair of
+    /// non-negative numbers) are treated as empty tiles. Use `validate` to find them.
+    pub fn unpack(&self) -> TileCoordsIter<'_> {
+        let cells = match &self.data {
+            TileCoordsLayerStorage::DataCoords(data) => Cells::flat(data, self.grid_cells_x),
+            TileCoordsLayerStorage::DataCoords2D(data) => Cells::rows(data),
+        };
+
+        TileCoordsIter {
+            cells,
+            cell_size: self.cell_size(),
+        }
+    }
 
-                    let (grid_coords, pixel_coords) = if coords[0] == -1 {
-                        (None, None)
-                    } else {
-                        let grid_u = coords[0];
-                        let grid_v = coords[1];
+    /// Unpack the tile data from the layer, consuming it.
+    ///
+    /// This yields the same tiles as `unpack`, but the returned iterator does not
+    /// borrow the layer, so it can be stored or sent to another thread.
+    pub fn into_unpack(self) -> impl ExactSizeIterator<Item = TileCoords> {
+        let cell_size = self.cell_size();
+        let cells_x = self.grid_cells_x;
 
-                        let pixel_u = grid_u * self.grid_cell_width;
-                        let pixel_v = grid_v * self.grid_cell_height;
+        match self.data {
+            TileCoordsLayerStorage::DataCoords(data) => {
+                let data = if cells_x > 0 { data } else { Vec::new() };
 
-                        (
-                            Some(Vec2 {
-                                x: grid_u,
-                                y: grid_v,
-                            }),
-                            Some(Vec2 {
-                                x: pixel_u,
-                                y: pixel_v,
-                            }),
-                        )
-                    };
+                Either::Left(data.into_iter().enumerate().map(move |(i, coords)| {
+                    let grid_x = i as i32 % cells_x;
+                    let grid_y = i as i32 / cells_x;
 
-                    TileCoords {
-                        grid_coords,
-                        pixel_coords,
-                        grid_position: Vec2 {
-                            x: grid_x,
-                            y: grid_y,
-                        },
-                        pixel_position: Vec2 {
-                            x: pixel_x,
-                            y: pixel_y,
-                        },
-                    }
+                    unpack_tile_coords(cell_size, grid_x, grid_y, &coords)
                 }))
             }
 
             TileCoordsLayerStorage::DataCoords2D(data) => {
-                Either::Right(data.iter().enumerate().flat_map(move |(y, row)| {
-                    row.iter().enumerate().map(move |(x, coords)| {
-                        let grid_x = x as i32;
-                        let grid_y = y as i32;
+                let len = data.iter().map(Vec::len).sum();
+
+                Either::Right(ExactLen::new(
+                    len,
+                    data.into_iter().enumerate().flat_map(move |(y, row)| {
+                        row.into_iter().enumerate().map(move |(x, coords)| {
+                            unpack_tile_coords(cell_size, x as i32, y as i32, &coords)
+                        })
+                    }),
+                ))
+            }
+        }
+    }
 
-                        let pixel_x = grid_x * self.grid_cell_width;
-                        let pixel_y = grid_y * self.grid_cell_height;
+    /// Unpack the tile data from the layer, with the layer's offset applied to
+    /// the pixel positions.
+    ///
+    /// The offset is truncated to a whole number of pixels.
+    pub fn unpack_with_offset(&self) -> impl ExactSizeIterator<Item = TileCoords> + '_ {
+        self.unpack_with_world_offset(Vec2 { x: 0.0, y: 0.0 })
+    }
 
-                        let (grid_coords, pixel_coords) = if coords[0] == -1 {
-                            (None, None)
-                        } else {
-                            let grid_u = coords[0];
-                            let grid_v = coords[1];
-
-                            let pixel_u = grid_u * self.grid_cell_width;
-                            let pixel_v = grid_v * self.grid_cell_height;
-
-                            (
-                                Some(Vec2 {
-                                    x: grid_u,
-                                    y: grid_v,
-                                }),
-                                Some(Vec2 {
-                                    x: pixel_u,
-                                    y: pixel_v,
-                                }),
-                            )
-                        };
+    /// Unpack the tile data from the layer, with both the layer's offset and the
+    /// given world offset applied to the pixel positions.
+    ///
+    /// Passing in `Level::world_offset` gives the absolute position of each tile, which
+    /// is useful when stitching together chunked levels. The combined offset is
+    /// truncated to a whole number of pixels.
+    pub fn unpack_with_world_offset(
+        &self,
+        world_offset: Vec2<f32>,
+    ) -> impl ExactSizeIterator<Item = TileCoords> + '_ {
+        let offset_x = (self.offset_x + world_offset.x) as i32;
+        let offset_y = (self.offset_y + world_offset.y) as i32;
+
+        self.unpack().map(move |mut tile| {
+            tile.pixel_position.x += offset_x;
+            tile.pixel_position.y += offset_y;
+            tile
+        })
+    }
 
-                        TileCoords {
-                            grid_coords,
-                            pixel_coords,
-                            grid_position: Vec2 {
-                                x: grid_x,
-                                y: grid_y,
-                            },
-                            pixel_position: Vec2 {
-                                x: pixel_x,
-                                y: pixel_y,
-                            },
-                        }
+    /// Gets the tile at the given grid position, without unpacking the whole layer.
+    ///
+    /// Returns `None` if the position is out of bounds, or if its pixel position does
+    /// not fit in an `i32`.
+    pub fn get(&self, grid_x: i32, grid_y: i32) -> Option<TileCoords> {
+        if !in_grid(self.grid_cells_x, self.grid_cells_y, grid_x, grid_y) {
+            return None;
+        }
+
+        let coords = match &self.data {
+            TileCoordsLayerStorage::DataCoords(data) => {
+                data.get(flat_index(self.grid_cells_x, grid_x, grid_y)?)?
+            }
+            TileCoordsLayerStorage::DataCoords2D(data) => {
+                data.get(grid_y as usize)?.get(grid_x as usize)?
+            }
+        };
+
+        // Checked before unpacking, which calculates the same position without checking.
+        pixel_position(self.cell_size(), grid_x, grid_y)?;
+
+        Some(unpack_tile_coords(self.cell_size(), grid_x, grid_y, coords))
+    }
+
+    /// Gets the name of the tileset used for this layer.
+    ///
+    /// Returns `None` if the layer does not have a tileset assigned.
+    pub fn tileset_name(&self) -> Option<&str> {
+        if self.tileset.is_empty() {
+            None
+        } else {
+            Some(&self.tileset)
+        }
+    }
+
+    /// Checks that every tile in the layer is either `[-1]` or `[-1, -1]` (empty), or a
+    /// pair of non-negative tile co-ordinates.
+    ///
+    /// # Errors
+    ///
+    /// If any malformed tiles are found, all of them will be returned, rather than just the first.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let malformed = |grid_x: i32, grid_y: i32, coords: &[i32]| {
+            if is_well_formed_tile_coords(coords) {
+                None
+            } else {
+                Some(ValidationError::MalformedTileCoords {
+                    layer: self.name.clone(),
+                    grid_position: Vec2 {
+                        x: grid_x,
+                        y: grid_y,
+                    },
+                    coords: coords.to_vec(),
+                })
+            }
+        };
+
+        let errors: Vec<_> = match &self.data {
+            TileCoordsLayerStorage::DataCoords(data) => {
+                let data: &[Vec<i32>] = if self.grid_cells_x > 0 { data } else { &[] };
+
+                data.iter()
+                    .enumerate()
+                    .filter_map(|(i, coords)| {
+                        let grid_x = i as i32 % self.grid_cells_x;
+                        let grid_y = i as i32 / self.grid_cells_x;
+
+                        malformed(grid_x, grid_y, coords)
                     })
-                }))
+                    .collect()
             }
+
+            TileCoordsLayerStorage::DataCoords2D(data) => data
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.iter()
+                        .enumerate()
+                        .filter_map(move |(x, coords)| malformed(x as i32, y as i32, coords))
+                })
+                .collect(),
+        };
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn cell_size(&self) -> Vec2<i32> {
+        Vec2 {
+            x: self.grid_cell_width,
+            y: self.grid_cell_height,
         }
     }
 }
 
+/// Returns whether the raw co-ords are either empty or a valid pair of tile co-ords.
+fn is_well_formed_tile_coords(coords: &[i32]) -> bool {
+    match *coords {
+        [-1] | [-1, -1] => true,
+        [u, v] => u >= 0 && v >= 0,
+        _ => false,
+    }
+}
+
+/// Creates a tile from the raw co-ords stored at the given grid position.
+fn unpack_tile_coords(
+    cell_size: Vec2<i32>,
+    grid_x: i32,
+    grid_y: i32,
+    coords: &[i32],
+) -> TileCoords {
+    let grid_coords = match *coords {
+        [u, v] if u >= 0 && v >= 0 => Some(Vec2 { x: u, y: v }),
+        _ => None,
+    };
+
+    TileCoords {
+        grid_coords,
+        pixel_coords: grid_coords.map(|c| Vec2 {
+            x: c.x * cell_size.x,
+            y: c.y * cell_size.y,
+        }),
+        grid_position: Vec2 {
+            x: grid_x,
+            y: grid_y,
+        },
+        pixel_position: Vec2 {
+            x: grid_x * cell_size.x,
+            y: grid_y * cell_size.y,
+        },
+    }
+}
+
 /// An individual tile, unpacked from a `TileCoordsLayer`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct TileCoords {
     /// The position of the tile in the tileset, in grid co-ordinates.
     ///
@@ -550,7 +3642,7 @@ pub struct TileCoords {
 }
 
 /// Tile data from a `TileCoordsLayer`.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub enum TileCoordsLayerStorage {
     /// A flat list of tile co-ords.
     ///
@@ -558,7 +3650,7 @@ pub enum TileCoordsLayerStorage {
     /// values are cell-based, rather than pixel-based - multiply by `grid_cell_width`
     /// to get the pixel position.
     ///
-    /// Empty tiles are represented by a `[-1]`.
+    /// Empty tiles are represented by a `[-1]` (or `[-1, -1]`, in some exports).
     #[serde(rename = "dataCoords")]
     DataCoords(Vec<Vec<i32>>),
 
@@ -568,11 +3660,63 @@ pub enum TileCoordsLayerStorage {
     /// values are cell-based, rather than pixel-based - multiply by `grid_cell_width`
     /// to get the pixel position.
     ///
-    /// Empty tiles are represented by a `[-1]`.
+    /// Empty tiles are represented by a `[-1]` (or `[-1, -1]`, in some exports).
     #[serde(rename = "dataCoords2D")]
     DataCoords2D(Vec<Vec<Vec<i32>>>),
 }
 
+impl TileCoordsLayerStorage {
+    /// Returns the encoding used for empty tiles in this data.
+    ///
+    /// The editor writes empty tiles as `[-1]`, but some exports use `[-1, -1]` instead.
+    /// The raw data is always written back out unchanged, so this is used to make sure
+    /// that any empty tiles added by the crate (e.g. when padding ragged rows) match the
+    /// existing ones. If the data has no empty tiles, `[-1]` is returned.
+    pub fn empty_coords(&self) -> Vec<i32> {
+        let is_empty = |coords: &&Vec<i32>| matches!(coords.as_slice(), [-1] | [-1, -1]);
+
+        let found = match self {
+            TileCoordsLayerStorage::DataCoords(data) => data.iter().find(is_empty),
+            TileCoordsLayerStorage::DataCoords2D(data) => data.iter().flatten().find(is_empty),
+        };
+
+        found.cloned().unwrap_or_else(|| vec![-1])
+    }
+
+    /// Converts the data into a flat list, if it is not already stored that way.
+    ///
+    /// `cells_x` is the number of cells in each row - this will usually be
+    /// the `grid_cells_x` of the layer that the data belongs to. Each row is padded
+    /// with empty tiles (as returned by `empty_coords`) or truncated to this length,
+    /// so that ragged rows don't shift the cells that come after them.
+    pub fn to_1d(&self, cells_x: usize) -> TileCoordsLayerStorage {
+        match self {
+            TileCoordsLayerStorage::DataCoords(data) => {
+                TileCoordsLayerStorage::DataCoords(data.clone())
+            }
+            TileCoordsLayerStorage::DataCoords2D(data) => {
+                let empty = self.empty_coords();
+                TileCoordsLayerStorage::DataCoords(flatten_rows(data, cells_x, empty))
+            }
+        }
+    }
+
+    /// Converts the data into a 2D list, if it is not already stored that way.
+    ///
+    /// `cells_x` is the number of cells in each row - this will usually be
+    /// the `grid_cells_x` of the layer that the data belongs to.
+    pub fn to_2d(&self, cells_x: usize) -> TileCoordsLayerStorage {
+        match self {
+            TileCoordsLayerStorage::DataCoords(data) => {
+                TileCoordsLayerStorage::DataCoords2D(to_rows(data, cells_x))
+            }
+            TileCoordsLayerStorage::DataCoords2D(data) => {
+                TileCoordsLayerStorage::DataCoords2D(data.clone())
+            }
+        }
+    }
+}
+
 impl Serialize for TileCoordsLayerStorage {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -598,7 +3742,7 @@ impl Serialize for TileCoordsLayerStorage {
 }
 
 /// A grid layer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GridLayer {
     /// The name of the layer.
@@ -609,9 +3753,11 @@ pub struct GridLayer {
     pub export_id: String,
 
     /// The layer's offset on the X axis.
+    #[serde(default)]
     pub offset_x: f32,
 
     /// The layer's offset on the Y axis.
+    #[serde(default)]
     pub offset_y: f32,
 
     /// The width of the layer's grid cells.
@@ -634,7 +3780,7 @@ pub struct GridLayer {
 }
 
 /// Grid data from a `GridLayer`.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub enum GridLayerStorage {
     /// A flat list of string data.
     ///
@@ -649,6 +3795,34 @@ pub enum GridLayerStorage {
     Grid2D(Vec<Vec<String>>),
 }
 
+impl GridLayerStorage {
+    /// Converts the data into a flat list, if it is not already stored that way.
+    ///
+    /// `cells_x` is the number of cells in each row - this will usually be
+    /// the `grid_cells_x` of the layer that the data belongs to. Each row is padded
+    /// with `"0"` cells or truncated to this length, so that ragged rows don't
+    /// shift the cells that come after them.
+    pub fn to_1d(&self, cells_x: usize) -> GridLayerStorage {
+        match self {
+            GridLayerStorage::Grid(data) => GridLayerStorage::Grid(data.clone()),
+            GridLayerStorage::Grid2D(data) => {
+                GridLayerStorage::Grid(flatten_rows(data, cells_x, "0".to_owned()))
+            }
+        }
+    }
+
+    /// Converts the data into a 2D list, if it is not already stored that way.
+    ///
+    /// `cells_x` is the number of cells in each row - this will usually be
+    /// the `grid_cells_x` of the layer that the data belongs to.
+    pub fn to_2d(&self, cells_x: usize) -> GridLayerStorage {
+        match self {
+            GridLayerStorage::Grid(data) => GridLayerStorage::Grid2D(to_rows(data, cells_x)),
+            GridLayerStorage::Grid2D(data) => GridLayerStorage::Grid2D(data.clone()),
+        }
+    }
+}
+
 impl Serialize for GridLayerStorage {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -672,7 +3846,7 @@ impl Serialize for GridLayerStorage {
 }
 
 /// An individual grid cell, unpacked from a `GridLayer`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct GridCell<'a> {
     /// The value of the grid cell.
     ///
@@ -686,61 +3860,307 @@ pub struct GridCell<'a> {
     pub pixel_position: Vec2<i32>,
 }
 
+impl GridCell<'_> {
+    /// Gets the legend entry for the cell's value from the layer's template.
+    ///
+    /// Returns `None` if the value is not present in the legend.
+    pub fn describe<'t>(&self, template: &'t GridLayerTemplate) -> Option<&'t str> {
+        template.describe(self.value)
+    }
+}
+
+/// An individual grid cell, unpacked from a `GridLayer` by `GridLayer::into_unpack`.
+///
+/// This is the same as `GridCell`, but it owns its value rather than borrowing it
+/// from the layer.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OwnedGridCell {
+    /// The value of the grid cell.
+    ///
+    /// By default, `"0"` means 'empty', but this is customizable in the editor.
+    pub value: String,
+
+    /// The position of the cell in grid co-ordinates.
+    pub grid_position: Vec2<i32>,
+
+    /// The position of the cell in pixel co-ordinates.
+    pub pixel_position: Vec2<i32>,
+}
+
+impl OwnedGridCell {
+    /// Gets the legend entry for the cell's value from the layer's template.
+    ///
+    /// Returns `None` if the value is not present in the legend.
+    pub fn describe<'t>(&self, template: &'t GridLayerTemplate) -> Option<&'t str> {
+        template.describe(&self.value)
+    }
+}
+
 impl GridLayer {
     /// Unpack the grid data from the layer.
-    pub fn unpack(&self) -> impl Iterator<Item = GridCell<'_>> + '_ {
-        match &self.data {
-            GridLayerStorage::Grid(data) => {
-                Either::Left(data.iter().enumerate().map(move |(i, value)| {
-                    let grid_x = i as i32 % self.grid_cells_x;
-                    let grid_y = i as i32 / self.grid_cells_x;
+    ///
+    /// If the data is stored in 1D and `grid_cells_x` is zero, no cells will be yielded.
+    pub fn unpack(&self) -> GridCellIter<'_> {
+        let cells = match &self.data {
+            GridLayerStorage::Grid(data) => Cells::flat(data, self.grid_cells_x),
+            GridLayerStorage::Grid2D(data) => Cells::rows(data),
+        };
+
+        GridCellIter {
+            cells,
+            cell_size: Vec2 {
+                x: self.grid_cell_width,
+                y: self.grid_cell_height,
+            },
+        }
+    }
 
-                    let pixel_x = grid_x * self.grid_cell_width;
-                    let pixel_y = grid_y * self.grid_cell_height;
+    /// Unpack the grid data from the layer, consuming it.
+    ///
+    /// This yields the same cells as `unpack`, but the returned iterator does not
+    /// borrow the layer, so it can be stored or sent to another thread.
+    pub fn into_unpack(self) -> impl ExactSizeIterator<Item = OwnedGridCell> {
+        let cell_width = self.grid_cell_width;
+        let cell_height = self.grid_cell_height;
+        let cells_x = self.grid_cells_x;
+
+        let cell = move |grid_x: i32, grid_y: i32, value: String| OwnedGridCell {
+            value,
+            grid_position: Vec2 {
+                x: grid_x,
+                y: grid_y,
+            },
+            pixel_position: Vec2 {
+                x: grid_x * cell_width,
+                y: grid_y * cell_height,
+            },
+        };
+
+        match self.data {
+            GridLayerStorage::Grid(data) => {
+                let data = if cells_x > 0 { data } else { Vec::new() };
 
-                    GridCell {
-                        value,
-                        grid_position: Vec2 {
-                            x: grid_x,
-                            y: grid_y,
-                        },
-                        pixel_position: Vec2 {
-                            x: pixel_x,
-                            y: pixel_y,
-                        },
-                    }
-                }))
+                Either::Left(
+                    data.into_iter()
+                        .enumerate()
+                        .map(move |(i, value)| cell(i as i32 % cells_x, i as i32 / cells_x, value)),
+                )
             }
 
             GridLayerStorage::Grid2D(data) => {
-                Either::Right(data.iter().enumerate().flat_map(move |(y, row)| {
-                    row.iter().enumerate().map(move |(x, value)| {
-                        let grid_x = x as i32;
-                        let grid_y = y as i32;
-
-                        let pixel_x = grid_x * self.grid_cell_width;
-                        let pixel_y = grid_y * self.grid_cell_height;
-
-                        GridCell {
-                            value,
-                            grid_position: Vec2 {
-                                x: grid_x,
-                                y: grid_y,
-                            },
-                            pixel_position: Vec2 {
-                                x: pixel_x,
-                                y: pixel_y,
-                            },
-                        }
-                    })
-                }))
+                let len = data.iter().map(Vec::len).sum();
+
+                Either::Right(ExactLen::new(
+                    len,
+                    data.into_iter().enumerate().flat_map(move |(y, row)| {
+                        row.into_iter()
+                            .enumerate()
+                            .map(move |(x, value)| cell(x as i32, y as i32, value))
+                    }),
+                ))
+            }
+        }
+    }
+
+    /// Unpack the grid data from the layer, with the layer's offset applied to
+    /// the pixel positions.
+    ///
+    /// The offset is truncated to a whole number of pixels.
+    pub fn unpack_with_offset(&self) -> impl ExactSizeIterator<Item = GridCell<'_>> + '_ {
+        self.unpack_with_world_offset(Vec2 { x: 0.0, y: 0.0 })
+    }
+
+    /// Unpack the grid data from the layer, with both the layer's offset and the
+    /// given world offset applied to the pixel positions.
+    ///
+    /// Passing in `Level::world_offset` gives the absolute position of each cell, which
+    /// is useful when stitching together chunked levels. The combined offset is
+    /// truncated to a whole number of pixels.
+    pub fn unpack_with_world_offset(
+        &self,
+        world_offset: Vec2<f32>,
+    ) -> impl ExactSizeIterator<Item = GridCell<'_>> + '_ {
+        let offset_x = (self.offset_x + world_offset.x) as i32;
+        let offset_y = (self.offset_y + world_offset.y) as i32;
+
+        self.unpack().map(move |mut cell| {
+            cell.pixel_position.x += offset_x;
+            cell.pixel_position.y += offset_y;
+            cell
+        })
+    }
+
+    /// Iterates over the rows of the grid data, from top to bottom.
+    ///
+    /// This works regardless of how the data is stored, and is cheaper than `unpack`
+    /// when you only need the values. If the data is stored in 2D and the rows are
+    /// not all the same length, each row will be yielded as-is. If the data is stored
+    /// in 1D and `grid_cells_x` is zero, no rows will be yielded.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &str> + '_> + '_ {
+        let rows = match &self.data {
+            GridLayerStorage::Grid(data) => {
+                let data: &[String] = if self.grid_cells_x > 0 { data } else { &[] };
+
+                Either::Left(data.chunks(self.grid_cells_x.max(1) as usize))
+            }
+
+            GridLayerStorage::Grid2D(data) => Either::Right(data.iter().map(Vec::as_slice)),
+        };
+
+        rows.map(|row| row.iter().map(String::as_str))
+    }
+
+    /// Gets the cell at the given grid position, without unpacking the whole layer.
+    ///
+    /// Returns `None` if the position is out of bounds, or if its pixel position does
+    /// not fit in an `i32`.
+    pub fn get(&self, grid_x: i32, grid_y: i32) -> Option<GridCell<'_>> {
+        if !in_grid(self.grid_cells_x, self.grid_cells_y, grid_x, grid_y) {
+            return None;
+        }
+
+        let value = match &self.data {
+            GridLayerStorage::Grid(data) => {
+                data.get(flat_index(self.grid_cells_x, grid_x, grid_y)?)?
             }
+            GridLayerStorage::Grid2D(data) => data.get(grid_y as usize)?.get(grid_x as usize)?,
+        };
+
+        let cell_size = Vec2 {
+            x: self.grid_cell_width,
+            y: self.grid_cell_height,
+        };
+
+        Some(GridCell {
+            value,
+            grid_position: Vec2 {
+                x: grid_x,
+                y: grid_y,
+            },
+            pixel_position: pixel_position(cell_size, grid_x, grid_y)?,
+        })
+    }
+
+    /// Creates a view of the layer, which allows cells to be looked up by grid position
+    /// without needing to handle the different storage modes.
+    pub fn view(&self) -> GridView<'_> {
+        GridView { layer: self }
+    }
+
+    /// Finds the values in the layer that do not have an entry in the template's legend.
+    ///
+    /// This is useful for catching cells that were painted with a value that has since
+    /// been removed from the legend. The empty value (`"0"` by default) is not treated
+    /// specially, so it will be included if the legend does not contain it.
+    pub fn undescribed_values<'a>(&'a self, template: &GridLayerTemplate) -> HashSet<&'a str> {
+        self.unpack()
+            .map(|cell| cell.value)
+            .filter(|value| template.describe(value).is_none())
+            .collect()
+    }
+
+    /// Counts the number of non-empty cells in the layer.
+    ///
+    /// Cells with a value of `"0"` are considered to be empty. This scans the raw data
+    /// directly, so it is cheaper than unpacking the layer.
+    pub fn filled_count(&self) -> usize {
+        match &self.data {
+            GridLayerStorage::Grid(data) => data.iter().filter(|v| *v != "0").count(),
+            GridLayerStorage::Grid2D(data) => data.iter().flatten().filter(|v| *v != "0").count(),
+        }
+    }
+
+    /// Removes any fully empty rows and columns from the edges of the layer.
+    ///
+    /// Cells with a value of `"0"` are considered to be empty. The layer's offset is
+    /// adjusted so that the remaining cells stay in the same position in the world.
+    /// If the layer is completely empty, it will be cropped down to zero cells.
+    ///
+    /// Returns the number of cells that were removed from the left and top edges,
+    /// and the new size of the layer in cells.
+    pub fn autocrop(&mut self) -> (Vec2<i32>, Vec2<i32>) {
+        let (min, size) = filled_bounds(
+            self.unpack()
+                .filter(|cell| cell.value != "0")
+                .map(|cell| cell.grid_position),
+        );
+
+        *self = self.subregion(min, size);
+
+        (min, size)
+    }
+
+    /// Creates a new layer containing a rectangular region of this layer's cells.
+    ///
+    /// `min` is the top left corner of the region and `size` is its size, both in
+    /// grid co-ordinates. The new layer's offset is adjusted so that its cells stay in
+    /// the same position in the world, and it will use the same storage mode as this
+    /// layer. Any part of the region that falls outside of this layer will be filled
+    /// with `"0"`.
+    pub fn subregion(&self, min: Vec2<i32>, size: Vec2<i32>) -> GridLayer {
+        let size = Vec2 {
+            x: size.x.max(0),
+            y: size.y.max(0),
+        };
+
+        let rows: Vec<Vec<String>> = (min.y..min.y + size.y)
+            .map(|y| {
+                (min.x..min.x + size.x)
+                    .map(|x| self.get(x, y).map_or("0", |cell| cell.value).to_owned())
+                    .collect()
+            })
+            .collect();
+
+        let data = match self.data {
+            GridLayerStorage::Grid(_) => GridLayerStorage::Grid(rows.concat()),
+            GridLayerStorage::Grid2D(_) => GridLayerStorage::Grid2D(rows),
+        };
+
+        GridLayer {
+            name: self.name.clone(),
+            export_id: self.export_id.clone(),
+            offset_x: self.offset_x + (min.x * self.grid_cell_width) as f32,
+            offset_y: self.offset_y + (min.y * self.grid_cell_height) as f32,
+            grid_cell_width: self.grid_cell_width,
+            grid_cell_height: self.grid_cell_height,
+            grid_cells_x: size.x,
+            grid_cells_y: size.y,
+            data,
+        }
+    }
+
+    /// Checks whether two layers have the same dimensions and cells, regardless of how
+    /// their data is stored.
+    ///
+    /// Other fields (such as the name and offset) are not compared. Missing cells are
+    /// treated as being empty (`"0"`).
+    pub fn content_eq(&self, other: &GridLayer) -> bool {
+        self.grid_cells_x == other.grid_cells_x
+            && self.grid_cells_y == other.grid_cells_y
+            && grid_positions(self.grid_cells_x, self.grid_cells_y).all(|(x, y)| {
+                self.get(x, y).map_or("0", |c| c.value) == other.get(x, y).map_or("0", |c| c.value)
+            })
+    }
+
+    /// Gets the fraction of the layer's cells that are not set to the given empty value,
+    /// from `0.0` to `1.0`.
+    ///
+    /// If the layer has no cells, this will return `0.0`.
+    pub fn coverage(&self, empty: &str) -> f32 {
+        let cells = self.grid_cells_x * self.grid_cells_y;
+
+        if cells <= 0 {
+            return 0.0;
         }
+
+        let filled = self.unpack().filter(|c| c.value != empty).count();
+
+        filled as f32 / cells as f32
     }
 }
 
 /// An entity layer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EntityLayer {
     /// The name of the layer.
@@ -751,9 +4171,11 @@ pub struct EntityLayer {
     pub export_id: String,
 
     /// The layer's offset on the X axis.
+    #[serde(default)]
     pub offset_x: f32,
 
     /// The layer's offset on the Y axis.
+    #[serde(default)]
     pub offset_y: f32,
 
     /// The width of the layer's grid cells.
@@ -772,8 +4194,26 @@ pub struct EntityLayer {
     pub entities: Vec<Entity>,
 }
 
+impl EntityLayer {
+    /// Returns an iterator over the entities in the layer whose template has the given tag.
+    ///
+    /// As tags are only stored on the entity templates, the project is needed to look them
+    /// up. Entities that do not have a matching template in the project are skipped.
+    pub fn entities_with_tag<'a>(
+        &'a self,
+        project: &'a Project,
+        tag: &'a str,
+    ) -> impl Iterator<Item = &'a Entity> {
+        self.entities.iter().filter(move |entity| {
+            project
+                .entity_template_by_name(&entity.name)
+                .is_some_and(|template| template.tags.iter().any(|t| t == tag))
+        })
+    }
+}
+
 /// A decal layer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DecalLayer {
     /// The name of the layer.
@@ -784,9 +4224,11 @@ pub struct DecalLayer {
     pub export_id: String,
 
     /// The layer's offset on the X axis.
+    #[serde(default)]
     pub offset_x: f32,
 
     /// The layer's offset on the Y axis.
+    #[serde(default)]
     pub offset_y: f32,
 
     /// The width of the layer's grid cells.
@@ -805,5 +4247,5 @@ pub struct DecalLayer {
     pub decals: Vec<Decal>,
 
     /// The path containing the decal images, relative to the project.
-    pub folder: PathBuf,
+    pub folder: String,
 }
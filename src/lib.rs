@@ -1,49 +1,410 @@
 //! `ogmo3` is a Rust crate for parsing projects and levels created with [Ogmo Editor 3](https://ogmo-editor-3.github.io/).
 
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+compile_error!("ogmo3 requires either the `std` or `hashbrown` feature to be enabled");
 
 pub mod level;
 pub mod project;
 
+mod base64;
+mod json;
+
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops::{Add, Mul};
+#[cfg(feature = "std")]
 use std::error::Error as StdError;
-use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
 pub use level::{Layer, Level, Value};
 pub use project::Project;
 
+/// The hash map type used throughout the crate.
+///
+/// This is `hashbrown::HashMap` by default, or `std::collections::HashMap` if the
+/// `hashbrown` feature is disabled. Without the `std` feature, `hashbrown` must be enabled.
+#[cfg(feature = "hashbrown")]
+pub type HashMap<K, V> = hashbrown::HashMap<K, V>;
+
+/// The hash map type used throughout the crate.
+///
+/// This is `hashbrown::HashMap` by default, or `std::collections::HashMap` if the
+/// `hashbrown` feature is disabled. Without the `std` feature, `hashbrown` must be enabled.
+#[cfg(not(feature = "hashbrown"))]
+pub type HashMap<K, V> = std::collections::HashMap<K, V>;
+
+/// The map type used for custom values and grid legends.
+///
+/// This is `HashMap` by default, which is written out with its keys in sorted order.
+/// If the `preserve-order` feature is enabled, this is `indexmap::IndexMap` instead,
+/// which keeps the order that the entries were parsed or inserted in.
+#[cfg(not(feature = "preserve-order"))]
+pub type ValueMap<K, V> = HashMap<K, V>;
+
+/// The map type used for custom values and grid legends.
+///
+/// This is `HashMap` by default, which is written out with its keys in sorted order.
+/// If the `preserve-order` feature is enabled, this is `indexmap::IndexMap` instead,
+/// which keeps the order that the entries were parsed or inserted in.
+#[cfg(feature = "preserve-order")]
+pub type ValueMap<K, V> = indexmap::IndexMap<K, V>;
+
+/// Removes an entry from a `ValueMap`, keeping the remaining entries in order.
+#[cfg(not(feature = "preserve-order"))]
+pub(crate) fn remove_entry<V>(map: &mut ValueMap<String, V>, key: &str) -> Option<V> {
+    map.remove(key)
+}
+
+/// Removes an entry from a `ValueMap`, keeping the remaining entries in order.
+#[cfg(feature = "preserve-order")]
+pub(crate) fn remove_entry<V>(map: &mut ValueMap<String, V>, key: &str) -> Option<V> {
+    map.shift_remove(key)
+}
+
+/// The set type returned by queries over layer data.
+///
+/// This is `hashbrown::HashSet` by default, or `std::collections::HashSet` if the
+/// `hashbrown` feature is disabled, matching `HashMap`.
+#[cfg(feature = "hashbrown")]
+pub type HashSet<T> = hashbrown::HashSet<T>;
+
+/// The set type returned by queries over layer data.
+///
+/// This is `hashbrown::HashSet` by default, or `std::collections::HashSet` if the
+/// `hashbrown` feature is disabled, matching `HashMap`.
+#[cfg(not(feature = "hashbrown"))]
+pub type HashSet<T> = std::collections::HashSet<T>;
+
+/// Runs a parsing function on tokio's blocking thread pool, so that it doesn't stall
+/// the async runtime.
+#[cfg(feature = "tokio")]
+pub(crate) async fn parse_blocking<T, F>(parse: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(parse).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(Error::Io(io::Error::other(e))),
+    }
+}
+
 /// The various kinds of errors that can occur while parsing Ogmo data.
 #[derive(Debug)]
 pub enum Error {
     /// An IO error was encountered.
+    ///
+    /// This is only available if the `std` feature is enabled.
+    #[cfg(feature = "std")]
     Io(io::Error),
 
     /// An error was encountered while deserializing JSON.
     Json(serde_json::Error),
+
+    /// The data was well-formed, but was inconsistent with itself or with the project.
+    Inconsistent(String),
+
+    /// A string could not be parsed as a hex color.
+    InvalidColor(String),
+
+    /// An embedded image could not be decoded from base 64.
+    Base64(String),
+
+    /// The data was exported by a version of Ogmo that this crate does not support.
+    ///
+    /// This is only returned by the `check_version` methods - the crate will not check
+    /// the version while parsing.
+    UnsupportedVersion {
+        /// The version that the data was exported with.
+        found: String,
+
+        /// The range of versions that this crate supports.
+        supported: &'static str,
+    },
+
+    /// An error was encountered while loading the level file at the given path.
+    ///
+    /// This is only available if the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    Level(PathBuf, Box<Error>),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Error::Io(_) => write!(f, "IO error"),
-            Error::Json(_) => write!(f, "JSON error"),
+            #[cfg(feature = "std")]
+            Error::Io(cause) => write!(f, "IO error: {}", cause),
+            Error::Json(cause) => write!(f, "JSON error: {}", cause),
+            Error::Inconsistent(message) => write!(f, "Inconsistent data: {}", message),
+            Error::InvalidColor(value) => write!(f, "Invalid color: '{}'", value),
+            Error::Base64(message) => write!(f, "Base 64 error: {}", message),
+            Error::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Unsupported Ogmo version '{}' (supported versions: {})",
+                found, supported
+            ),
+            #[cfg(feature = "std")]
+            Error::Level(path, cause) => {
+                write!(f, "Error loading level '{}': {}", path.display(), cause)
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Error::Io(cause) => Some(cause),
             Error::Json(cause) => Some(cause),
+            Error::Inconsistent(_) => None,
+            Error::InvalidColor(_) => None,
+            Error::Base64(_) => None,
+            Error::UnsupportedVersion { .. } => None,
+            Error::Level(_, cause) => Some(cause.as_ref()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(cause: io::Error) -> Error {
+        Error::Io(cause)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(cause: serde_json::Error) -> Error {
+        Error::Json(cause)
+    }
+}
+
+/// A problem that was found while validating a project or level.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// The default value of a color value template is not a valid hex color.
+    InvalidColorDefault {
+        /// The name of the value template.
+        template: String,
+
+        /// The invalid default value.
+        value: String,
+    },
+
+    /// The default value of an enum value template is not a valid index into its choices.
+    InvalidEnumDefault {
+        /// The name of the value template.
+        template: String,
+
+        /// The invalid default index.
+        index: i32,
+
+        /// The number of choices that the enum has.
+        choices: usize,
+    },
+
+    /// A layer in a level does not match any of the project's layer templates.
+    UnknownLayerTemplate {
+        /// The name of the layer.
+        layer: String,
+
+        /// The export ID that the layer refers to.
+        export_id: String,
+    },
+
+    /// A layer in a level refers to a tileset that is not in the project.
+    UnknownTileset {
+        /// The name of the layer.
+        layer: String,
+
+        /// The label of the tileset that the layer refers to.
+        tileset: String,
+    },
+
+    /// An entity in a level does not match any of the project's entity templates.
+    UnknownEntityTemplate {
+        /// The name of the layer containing the entity.
+        layer: String,
+
+        /// The name of the entity.
+        entity: String,
+    },
+
+    /// A custom value does not match the type of its value template.
+    MismatchedValue {
+        /// The name of the value template.
+        template: String,
+
+        /// The invalid value, formatted as JSON.
+        value: String,
+    },
+
+    /// A numeric custom value is outside of its value template's bounds.
+    ValueOutOfBounds {
+        /// The name of the value template.
+        template: String,
+
+        /// The invalid value, formatted as JSON.
+        value: String,
+
+        /// The template's minimum value.
+        min: f64,
+
+        /// The template's maximum value.
+        max: f64,
+    },
+
+    /// An enum custom value does not match any of its value template's choices.
+    InvalidEnumValue {
+        /// The name of the value template.
+        template: String,
+
+        /// The invalid value, formatted as JSON.
+        value: String,
+
+        /// The number of choices that the enum has.
+        choices: usize,
+    },
+
+    /// A string custom value is longer than its value template's maximum length.
+    StringTooLong {
+        /// The name of the value template.
+        template: String,
+
+        /// The length of the value, in characters.
+        length: usize,
+
+        /// The template's maximum length.
+        max_length: i32,
+    },
+
+    /// A tile in a tile co-ords layer is neither empty (`[-1]` or `[-1, -1]`) nor a pair of
+    /// non-negative co-ordinates.
+    MalformedTileCoords {
+        /// The name of the layer.
+        layer: String,
+
+        /// The position of the tile in the layer's grid.
+        grid_position: Vec2<i32>,
+
+        /// The malformed co-ordinates.
+        coords: Vec<i32>,
+    },
+
+    /// A tile layer's data does not match its declared grid size.
+    MismatchedDimensions {
+        /// The name of the layer.
+        layer: String,
+
+        /// A description of the mismatch.
+        message: String,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::InvalidColorDefault { template, value } => write!(
+                f,
+                "default for '{}' is not a valid color: '{}'",
+                template, value
+            ),
+            ValidationError::InvalidEnumDefault {
+                template,
+                index,
+                choices,
+            } => write!(
+                f,
+                "default for '{}' is out of range: {} (there are {} choices)",
+                template, index, choices
+            ),
+            ValidationError::UnknownLayerTemplate { layer, export_id } => write!(
+                f,
+                "layer '{}' refers to an unknown layer template: '{}'",
+                layer, export_id
+            ),
+            ValidationError::UnknownTileset { layer, tileset } => write!(
+                f,
+                "layer '{}' refers to an unknown tileset: '{}'",
+                layer, tileset
+            ),
+            ValidationError::UnknownEntityTemplate { layer, entity } => write!(
+                f,
+                "layer '{}' contains an unknown entity: '{}'",
+                layer, entity
+            ),
+            ValidationError::MismatchedValue { template, value } => write!(
+                f,
+                "value for '{}' does not match its type: {}",
+                template, value
+            ),
+            ValidationError::ValueOutOfBounds {
+                template,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "value for '{}' is out of bounds: {} (must be between {} and {})",
+                template, value, min, max
+            ),
+            ValidationError::InvalidEnumValue {
+                template,
+                value,
+                choices,
+            } => write!(
+                f,
+                "value for '{}' is not a valid choice: {} (there are {} choices)",
+                template, value, choices
+            ),
+            ValidationError::StringTooLong {
+                template,
+                length,
+                max_length,
+            } => write!(
+                f,
+                "value for '{}' is too long: {} characters (the maximum is {})",
+                template, length, max_length
+            ),
+            ValidationError::MalformedTileCoords {
+                layer,
+                grid_position,
+                coords,
+            } => write!(
+                f,
+                "layer '{}' has malformed tile co-ordinates at ({}, {}): {:?}",
+                layer, grid_position.x, grid_position.y, coords
+            ),
+            ValidationError::MismatchedDimensions { layer, message } => {
+                write!(
+                    f,
+                    "layer '{}' does not match its grid size: {}",
+                    layer, message
+                )
+            }
         }
     }
 }
 
 /// An X and Y value.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+///
+/// `Eq` and `Hash` are only implemented when `T` implements them, so `Vec2<i32>`
+/// can be used as a map key, but `Vec2<f32>` cannot.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
 pub struct Vec2<T> {
     /// The X component.
     pub x: T,
@@ -51,3 +412,219 @@ pub struct Vec2<T> {
     /// The Y component.
     pub y: T,
 }
+
+impl<T> Vec2<T> {
+    /// Applies a function to each component of the vector, returning a new vector.
+    ///
+    /// This is useful for converting between `Vec2<i32>` and `Vec2<f32>`.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Vec2<U> {
+        Vec2 {
+            x: f(self.x),
+            y: f(self.y),
+        }
+    }
+
+    /// Converts the vector into an array of `[x, y]`.
+    ///
+    /// This is useful for packing positions into GPU buffers.
+    pub fn to_array(self) -> [T; 2] {
+        [self.x, self.y]
+    }
+}
+
+impl<T: Clone> Vec2<T> {
+    /// Creates a vector with both components set to the given value.
+    pub fn splat(v: T) -> Vec2<T> {
+        Vec2 { x: v.clone(), y: v }
+    }
+}
+
+impl<T: Default> Vec2<T> {
+    /// Creates a vector with both components set to their default value, which is zero
+    /// for numeric types.
+    pub fn zero() -> Vec2<T> {
+        Vec2 {
+            x: T::default(),
+            y: T::default(),
+        }
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Vec2<T> {
+    /// Calculates the dot product of two vectors.
+    pub fn dot(self, other: Vec2<T>) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Calculates the squared length of the vector.
+    ///
+    /// This avoids a square root, so it is cheaper than calculating the actual length
+    /// when comparing distances.
+    pub fn length_squared(self) -> T {
+        self.dot(self)
+    }
+}
+
+impl<T> From<[T; 2]> for Vec2<T> {
+    fn from([x, y]: [T; 2]) -> Vec2<T> {
+        Vec2 { x, y }
+    }
+}
+
+impl<T> From<Vec2<T>> for [T; 2] {
+    fn from(v: Vec2<T>) -> [T; 2] {
+        v.to_array()
+    }
+}
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Vec2<T> {
+        Vec2 { x, y }
+    }
+}
+
+impl<T> From<Vec2<T>> for (T, T) {
+    fn from(v: Vec2<T>) -> (T, T) {
+        (v.x, v.y)
+    }
+}
+
+/// Options for pretty-printing levels and projects as JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// The string used for each level of indentation.
+    ///
+    /// Defaults to two spaces. Some versions of Ogmo indent with tabs (`"\t"`).
+    pub indent: String,
+
+    /// Whether the keys of each object should be written in alphabetical order, rather
+    /// than in the order they are declared in.
+    ///
+    /// Defaults to `false`.
+    pub sort_keys: bool,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> PrettyOptions {
+        PrettyOptions {
+            indent: "  ".into(),
+            sort_keys: false,
+        }
+    }
+}
+
+/// A rectangle, defined by the position of its top left corner and its size.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Rect<T> {
+    /// The X position of the rectangle.
+    pub x: T,
+
+    /// The Y position of the rectangle.
+    pub y: T,
+
+    /// The width of the rectangle.
+    pub width: T,
+
+    /// The height of the rectangle.
+    pub height: T,
+}
+
+/// An RGBA color.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Color {
+    /// The red component of the color.
+    pub r: u8,
+
+    /// The green component of the color.
+    pub g: u8,
+
+    /// The blue component of the color.
+    pub b: u8,
+
+    /// The alpha component of the color.
+    pub a: u8,
+}
+
+impl Color {
+    /// Parses a hex color in the form `#rgb`, `#rrggbb` or `#rrggbbaa`, as used by Ogmo.
+    ///
+    /// If the alpha component is not specified, it will be set to `255`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidColor` will be returned if the string is not a valid hex color.
+    pub fn parse(s: &str) -> Result<Color, Error> {
+        let invalid = || Error::InvalidColor(s.to_owned());
+
+        let hex = s.strip_prefix('#').ok_or_else(invalid)?;
+
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let channel = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).unwrap_or(0);
+
+            // Short-form channels are repeated, so `f` means `ff`.
+            if len == 1 {
+                value * 0x11
+            } else {
+                value
+            }
+        };
+
+        match hex.len() {
+            3 => Ok(Color {
+                r: channel(0, 1),
+                g: channel(1, 1),
+                b: channel(2, 1),
+                a: 255,
+            }),
+            6 => Ok(Color {
+                r: channel(0, 2),
+                g: channel(1, 2),
+                b: channel(2, 2),
+                a: 255,
+            }),
+            8 => Ok(Color {
+                r: channel(0, 2),
+                g: channel(1, 2),
+                b: channel(2, 2),
+                a: channel(3, 2),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Converts the color to a hex string, in the form `#rrggbbaa`.
+    pub fn to_hex_string(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+}
+
+/// The major version of Ogmo that this crate supports.
+const SUPPORTED_MAJOR_VERSION: u32 = 3;
+
+/// Checks that an Ogmo version string has a supported major version.
+pub(crate) fn check_version(version: &str) -> Result<(), Error> {
+    let major = version
+        .split('.')
+        .next()
+        .and_then(|major| major.trim().parse::<u32>().ok());
+
+    if major == Some(SUPPORTED_MAJOR_VERSION) {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedVersion {
+            found: version.to_owned(),
+            supported: "3.x",
+        })
+    }
+}
+
+/// Returns whether a float is finite and has no fractional part.
+///
+/// This is equivalent to `x.fract() == 0.0`, which is not available without `std`.
+pub(crate) fn is_whole(x: f32) -> bool {
+    x.is_finite() && (x.abs() >= 16_777_216.0 || x as i32 as f32 == x)
+}
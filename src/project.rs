@@ -1,16 +1,33 @@
 //! Functions and types for parsing Ogmo projects.
 
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::convert::TryFrom;
+#[cfg(feature = "std")]
 use std::fs;
+#[cfg(feature = "std")]
 use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
 
-use hashbrown::HashMap;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-use crate::{Error, Vec2};
+use crate::level::{
+    DecalLayer, Entity, EntityLayer, GridLayer, GridLayerStorage, Layer, Level, TileCoordsLayer,
+    TileCoordsLayerStorage, TileLayer, TileLayerStorage, TypedValue, Value,
+};
+use crate::{
+    base64, is_whole, json, Color, Error, PrettyOptions, Rect, ValidationError, ValueMap, Vec2,
+};
 
 /// An Ogmo project.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+///
+/// When comparing projects, floats are compared via `==`, and `base_directory` is
+/// taken into account.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Project {
     /// The name of the Ogmo project.
@@ -20,7 +37,7 @@ pub struct Project {
     pub ogmo_version: String,
 
     /// An array of paths that hold the project's levels.
-    pub level_paths: Vec<PathBuf>,
+    pub level_paths: Vec<String>,
 
     /// The project's background color.
     pub background_color: String,
@@ -66,16 +83,58 @@ pub struct Project {
 
     /// The project's tilesets.
     pub tilesets: Vec<Tileset>,
+
+    /// Levels that are embedded directly in the project file.
+    ///
+    /// This is not something that the Ogmo editor will write, but it allows a project
+    /// and its levels to be distributed as a single file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub levels: Vec<Level>,
+
+    /// The directory that the project was loaded from, if it was loaded via
+    /// `Project::from_file`.
+    ///
+    /// This is used to resolve the project's level paths, and is not written to the
+    /// project file. It is only available if the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[serde(skip)]
+    pub base_directory: Option<PathBuf>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 impl Project {
     /// Parses an Ogmo project from a JSON string.
     ///
+    /// A leading UTF-8 byte order mark is skipped, if there is one.
+    ///
     /// # Errors
     ///
     /// * `Error::Json` will be returned if deserialization fails.
     pub fn from_json(s: &str) -> Result<Project, Error> {
-        serde_json::from_str(s).map_err(Error::Json)
+        Project::from_slice(s.as_bytes())
+    }
+
+    /// Parses an Ogmo project from a slice of JSON bytes.
+    ///
+    /// This can be used to parse data that was loaded by other means, such as an
+    /// asynchronous file API, without converting it to a string first.
+    ///
+    /// A leading UTF-8 byte order mark is skipped, if there is one.
+    ///
+    /// As there is no path to resolve against, `base_directory` will not be set.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if deserialization fails.
+    pub fn from_slice(v: &[u8]) -> Result<Project, Error> {
+        serde_json::from_slice(json::strip_bom(v)).map_err(Error::Json)
     }
 
     /// Parses an Ogmo project from a file.
@@ -84,9 +143,38 @@ impl Project {
     ///
     /// * `Error::Io` will be returned if the file cannot be read.
     /// * `Error::Json` will be returned if deserialization fails.
+    #[cfg(feature = "std")]
     pub fn from_file(path: impl AsRef<Path>) -> Result<Project, Error> {
-        let json = fs::read_to_string(path).map_err(Error::Io)?;
-        Project::from_json(&json)
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)?;
+
+        let mut project = Project::from_json(&json)?;
+        project.base_directory = path.parent().map(Path::to_path_buf);
+
+        Ok(project)
+    }
+
+    /// Parses an Ogmo project from a file, without blocking the async runtime.
+    ///
+    /// The file is read via `tokio::fs`, and then parsed on tokio's blocking thread pool.
+    /// This must be called from within a tokio runtime. Like `from_file`, the directory
+    /// that the project was loaded from is recorded in `base_directory`.
+    ///
+    /// This is only available if the `tokio` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Io` will be returned if the file cannot be read.
+    /// * `Error::Json` will be returned if deserialization fails.
+    #[cfg(feature = "tokio")]
+    pub async fn from_file_async(path: impl AsRef<Path>) -> Result<Project, Error> {
+        let path = path.as_ref().to_path_buf();
+        let json = tokio::fs::read(&path).await?;
+
+        let mut project = crate::parse_blocking(move || Project::from_slice(&json)).await?;
+        project.base_directory = path.parent().map(Path::to_path_buf);
+
+        Ok(project)
     }
 
     /// Writes the Ogmo project to a JSON string.
@@ -95,7 +183,7 @@ impl Project {
     ///
     /// * `Error::Json` will be returned if serialization fails.
     pub fn to_json(&self) -> Result<String, Error> {
-        serde_json::to_string(self).map_err(Error::Json)
+        json::to_string(self).map_err(Error::Json)
     }
 
     /// Writes the Ogmo project to a pretty-printed JSON string.
@@ -104,12 +192,681 @@ impl Project {
     ///
     /// * `Error::Json` will be returned if serialization fails.
     pub fn to_json_pretty(&self) -> Result<String, Error> {
-        serde_json::to_string_pretty(self).map_err(Error::Json)
+        json::to_string_pretty(self).map_err(Error::Json)
+    }
+
+    /// Writes the Ogmo project to a pretty-printed JSON string, with the given indentation
+    /// and key ordering.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Json` will be returned if serialization fails.
+    pub fn to_json_pretty_with(&self, options: PrettyOptions) -> Result<String, Error> {
+        json::to_string_pretty_with(self, &options).map_err(Error::Json)
+    }
+
+    /// Builds a level from a layered tensor of tile IDs, indexed as `[layer][y][x]`.
+    ///
+    /// This is the inverse of `Level::to_tensor`. Each entry in the tensor is
+    /// converted into a tile layer, using the project's ID-based tile layer
+    /// templates (in the order they are defined) for the layer metadata. Empty
+    /// tiles should be represented by `-1`.
+    ///
+    /// `cell_size` sets the size of each grid cell in pixels, and is used to derive
+    /// the size of the level.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if the number of layers does not
+    ///   match the number of tile layer templates, or if the layers are not all
+    ///   rectangular and the same size.
+    pub fn level_from_tensor(
+        &self,
+        tensor: &[Vec<Vec<i32>>],
+        cell_size: Vec2<i32>,
+    ) -> Result<Level, Error> {
+        let templates: Vec<&TileLayerTemplate> = self
+            .layers
+            .iter()
+            .filter_map(|t| match t {
+                LayerTemplate::Tile(t) if matches!(t.export_mode, ExportMode::Ids) => Some(t),
+                _ => None,
+            })
+            .collect();
+
+        if tensor.len() != templates.len() {
+            return Err(Error::Inconsistent(format!(
+                "expected {} tile layers, found {}",
+                templates.len(),
+                tensor.len()
+            )));
+        }
+
+        let cells_y = tensor.first().map_or(0, Vec::len);
+        let cells_x = tensor
+            .first()
+            .and_then(|cells| cells.first())
+            .map_or(0, Vec::len);
+
+        for (i, cells) in tensor.iter().enumerate() {
+            if cells.len() != cells_y || cells.iter().any(|row| row.len() != cells_x) {
+                return Err(Error::Inconsistent(format!(
+                    "tensor layer {} is not {}x{} cells",
+                    i, cells_x, cells_y
+                )));
+            }
+        }
+
+        let layers = templates
+            .into_iter()
+            .zip(tensor)
+            .map(|(template, cells)| {
+                let data = match template.array_mode {
+                    ArrayMode::One => TileLayerStorage::Data(cells.concat()),
+                    ArrayMode::Two => TileLayerStorage::Data2D(cells.clone()),
+                };
+
+                Layer::Tile(TileLayer {
+                    name: template.name.clone(),
+                    export_id: template.export_id.clone(),
+                    offset_x: 0.0,
+                    offset_y: 0.0,
+                    grid_cell_width: cell_size.x,
+                    grid_cell_height: cell_size.y,
+                    grid_cells_x: cells_x as i32,
+                    grid_cells_y: cells_y as i32,
+                    tileset: template.default_tileset.clone(),
+                    data,
+                })
+            })
+            .collect();
+
+        Ok(Level {
+            ogmo_version: self.ogmo_version.clone(),
+            width: (cells_x as i32 * cell_size.x) as f32,
+            height: (cells_y as i32 * cell_size.y) as f32,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            values: ValueMap::new(),
+            layers,
+            extra: ValueMap::new(),
+        })
+    }
+
+    /// Checks that the project was exported by a version of Ogmo that this crate supports.
+    ///
+    /// The version is not checked when a project is loaded, so call this if you want to reject
+    /// projects from incompatible versions of the editor with a clear error.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsupportedVersion` will be returned if the project's major version is not supported.
+    pub fn check_version(&self) -> Result<(), Error> {
+        crate::check_version(&self.ogmo_version)
+    }
+
+    /// Checks the project for authoring mistakes.
+    ///
+    /// Currently, this checks that:
+    ///
+    /// * The defaults for color value templates are valid hex colors.
+    /// * The defaults for enum value templates are valid indexes into their choices.
+    ///
+    /// # Errors
+    ///
+    /// If any problems are found, all of them will be returned, rather than just the first.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let decal_values = self.layers.iter().flat_map(|layer| match layer {
+            LayerTemplate::Decal(layer) => layer.values.as_slice(),
+            _ => &[],
+        });
+
+        let entity_values = self.entities.iter().flat_map(|e| &e.values);
+
+        let errors: Vec<ValidationError> = self
+            .level_values
+            .iter()
+            .chain(decal_values)
+            .chain(entity_values)
+            .filter_map(|template| match template {
+                ValueTemplate::Color(t) if Color::parse(&t.defaults).is_err() => {
+                    Some(ValidationError::InvalidColorDefault {
+                        template: t.name.clone(),
+                        value: t.defaults.clone(),
+                    })
+                }
+
+                ValueTemplate::Enum(t)
+                    if t.defaults < 0 || t.defaults as usize >= t.choices.len() =>
+                {
+                    Some(ValidationError::InvalidEnumDefault {
+                        template: t.name.clone(),
+                        index: t.defaults,
+                        choices: t.choices.len(),
+                    })
+                }
+
+                _ => None,
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
+
+    /// Creates a view of the tileset with the given label, bound to the size of its texture.
+    ///
+    /// Returns `None` if there is no tileset with that label.
+    pub fn tileset_view(&self, label: &str, texture_size: Vec2<i32>) -> Option<TilesetView<'_>> {
+        self.tileset_by_label(label)
+            .map(|t| TilesetView::new(t, texture_size))
+    }
+
+    /// Gets the levels that are embedded directly in the project file.
+    ///
+    /// Returns an empty slice if the project has no embedded levels.
+    pub fn embedded_levels(&self) -> &[Level] {
+        &self.levels
+    }
+
+    /// Gets the tileset with the given label.
+    ///
+    /// This is the name that tile layers use to refer to their tileset.
+    pub fn tileset_by_label(&self, label: &str) -> Option<&Tileset> {
+        self.tilesets.iter().find(|t| t.label == label)
+    }
+
+    /// Gets a mutable reference to the tileset with the given label.
+    pub fn tileset_by_label_mut(&mut self, label: &str) -> Option<&mut Tileset> {
+        self.tilesets.iter_mut().find(|t| t.label == label)
+    }
+
+    /// Gets the entity template with the given name.
+    pub fn entity_template_by_name(&self, name: &str) -> Option<&EntityTemplate> {
+        self.entities.iter().find(|e| e.name == name)
+    }
+
+    /// Gets the entity template with the given export ID.
+    ///
+    /// This is the ID that entity instances store in their `export_id` field.
+    pub fn entity_template_by_export_id(&self, export_id: &str) -> Option<&EntityTemplate> {
+        self.entities.iter().find(|e| e.export_id == export_id)
+    }
+
+    /// Gets the layer template with the given name.
+    pub fn layer_template_by_name(&self, name: &str) -> Option<&LayerTemplate> {
+        self.layers.iter().find(|l| l.name() == name)
+    }
+
+    /// Gets the layer template with the given export ID.
+    ///
+    /// This is the ID that layer instances store in their `export_id` field.
+    pub fn layer_template_by_export_id(&self, export_id: &str) -> Option<&LayerTemplate> {
+        self.layers.iter().find(|l| l.export_id() == export_id)
+    }
+
+    /// Resolves the level path at the given index relative to the project's base
+    /// directory.
+    ///
+    /// If the project was not loaded via `Project::from_file`, the level path is
+    /// returned unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[cfg(feature = "std")]
+    pub fn resolve_level_path(&self, index: usize) -> PathBuf {
+        let level_path = &self.level_paths[index];
+
+        match &self.base_directory {
+            Some(base) => base.join(level_path),
+            None => PathBuf::from(level_path),
+        }
+    }
+
+    /// Returns an iterator over the project's level paths, resolved relative to the
+    /// project's base directory.
+    ///
+    /// If the project was not loaded via `Project::from_file`, the level paths are
+    /// returned unchanged.
+    #[cfg(feature = "std")]
+    pub fn resolved_level_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        (0..self.level_paths.len()).map(move |i| self.resolve_level_path(i))
+    }
+
+    /// Finds the level files in the project's level paths that have been modified since
+    /// the given time.
+    ///
+    /// As the level paths are relative to the project file, you must provide the path of
+    /// the directory containing the project. Subdirectories will be searched up to the
+    /// project's `directory_depth`, and any file with a `.json` extension is assumed to
+    /// be a level.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Io` will be returned if a directory or file's metadata could not be read.
+    #[cfg(feature = "std")]
+    pub fn levels_modified_since(
+        &self,
+        base: impl AsRef<Path>,
+        since: SystemTime,
+    ) -> Result<Vec<PathBuf>, Error> {
+        let mut modified = Vec::new();
+
+        for path in self.level_files(base.as_ref())? {
+            if fs::metadata(&path)?.modified()? > since {
+                modified.push(path);
+            }
+        }
+
+        Ok(modified)
+    }
+
+    /// Loads every level file in the project's level paths.
+    ///
+    /// As the level paths are relative to the project file, you must provide the path of
+    /// the directory containing the project. Subdirectories will be searched up to the
+    /// project's `directory_depth`, and any file with a `.json` extension is assumed to
+    /// be a level. The levels are returned alongside their paths, sorted by path.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Io` will be returned if a directory could not be read.
+    /// * `Error::Level` will be returned if a level file could not be loaded. This wraps
+    ///   the underlying error, along with the path of the offending file.
+    #[cfg(feature = "std")]
+    pub fn load_levels(&self, base: impl AsRef<Path>) -> Result<Vec<(PathBuf, Level)>, Error> {
+        self.level_files(base.as_ref())?
+            .into_iter()
+            .map(|path| match Level::from_file(&path) {
+                Ok(level) => Ok((path, level)),
+                Err(e) => Err(Error::Level(path, Box::new(e))),
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "std")]
+    fn level_files(&self, base: &Path) -> Result<Vec<PathBuf>, Error> {
+        let mut files = Vec::new();
+
+        for level_path in &self.level_paths {
+            find_level_files(&base.join(level_path), self.directory_depth, &mut files)?;
+        }
+
+        files.sort();
+        files.dedup();
+
+        Ok(files)
+    }
+
+    /// Gets the default custom values for a level in this project.
+    ///
+    /// These are taken from the project's `level_values` (see `ValueTemplate::default_value`),
+    /// and can be used to reset a level's values in the same way as the editor.
+    pub fn default_level_values(&self) -> ValueMap<String, Value> {
+        default_values(&self.level_values)
+    }
+
+    /// Creates a new, empty level, with one layer for each of the project's layer
+    /// templates.
+    ///
+    /// The size of each layer's grid is derived from the size of the level and the
+    /// template's grid size, and the layer's data is stored using the template's
+    /// export and array modes. The level's custom values are set to the defaults from
+    /// the project's level value templates (see `default_level_values`).
+    pub fn new_level(&self, width: f32, height: f32) -> Level {
+        let layers = self
+            .layers
+            .iter()
+            .map(|template| {
+                let name = template.name().to_owned();
+                let export_id = template.export_id().to_owned();
+
+                match template {
+                    LayerTemplate::Tile(t) => {
+                        let cells_x = cells_to_cover(width, t.grid_size.x);
+                        let cells_y = cells_to_cover(height, t.grid_size.y);
+                        let (cx, cy) = (cells_x as usize, cells_y as usize);
+
+                        match t.export_mode {
+                            ExportMode::Ids => Layer::Tile(TileLayer {
+                                name,
+                                export_id,
+                                offset_x: 0.0,
+                                offset_y: 0.0,
+                                grid_cell_width: t.grid_size.x,
+                                grid_cell_height: t.grid_size.y,
+                                grid_cells_x: cells_x,
+                                grid_cells_y: cells_y,
+                                tileset: t.default_tileset.clone(),
+                                data: match t.array_mode {
+                                    ArrayMode::One => TileLayerStorage::Data(vec![-1; cx * cy]),
+                                    ArrayMode::Two => {
+                                        TileLayerStorage::Data2D(vec![vec![-1; cx]; cy])
+                                    }
+                                },
+                            }),
+
+                            ExportMode::Coords => Layer::TileCoords(TileCoordsLayer {
+                                name,
+                                export_id,
+                                offset_x: 0.0,
+                                offset_y: 0.0,
+                                grid_cell_width: t.grid_size.x,
+                                grid_cell_height: t.grid_size.y,
+                                grid_cells_x: cells_x,
+                                grid_cells_y: cells_y,
+                                tileset: t.default_tileset.clone(),
+                                data: match t.array_mode {
+                                    ArrayMode::One => {
+                                        TileCoordsLayerStorage::DataCoords(vec![vec![-1]; cx * cy])
+                                    }
+                                    ArrayMode::Two => {
+                                        let row = vec![vec![-1]; cx];
+                                        TileCoordsLayerStorage::DataCoords2D(vec![row; cy])
+                                    }
+                                },
+                            }),
+                        }
+                    }
+
+                    LayerTemplate::Grid(t) => {
+                        let cells_x = cells_to_cover(width, t.grid_size.x);
+                        let cells_y = cells_to_cover(height, t.grid_size.y);
+                        let (cx, cy) = (cells_x as usize, cells_y as usize);
+
+                        Layer::Grid(GridLayer {
+                            name,
+                            export_id,
+                            offset_x: 0.0,
+                            offset_y: 0.0,
+                            grid_cell_width: t.grid_size.x,
+                            grid_cell_height: t.grid_size.y,
+                            grid_cells_x: cells_x,
+                            grid_cells_y: cells_y,
+                            data: match t.array_mode {
+                                ArrayMode::One => GridLayerStorage::Grid(vec!["0".into(); cx * cy]),
+                                ArrayMode::Two => {
+                                    GridLayerStorage::Grid2D(vec![vec!["0".into(); cx]; cy])
+                                }
+                            },
+                        })
+                    }
+
+                    LayerTemplate::Entity(t) => Layer::Entity(EntityLayer {
+                        name,
+                        export_id,
+                        offset_x: 0.0,
+                        offset_y: 0.0,
+                        grid_cell_width: t.grid_size.x,
+                        grid_cell_height: t.grid_size.y,
+                        grid_cells_x: cells_to_cover(width, t.grid_size.x),
+                        grid_cells_y: cells_to_cover(height, t.grid_size.y),
+                        entities: Vec::new(),
+                    }),
+
+                    LayerTemplate::Decal(t) => Layer::Decal(DecalLayer {
+                        name,
+                        export_id,
+                        offset_x: 0.0,
+                        offset_y: 0.0,
+                        grid_cell_width: t.grid_size.x,
+                        grid_cell_height: t.grid_size.y,
+                        grid_cells_x: cells_to_cover(width, t.grid_size.x),
+                        grid_cells_y: cells_to_cover(height, t.grid_size.y),
+                        decals: Vec::new(),
+                        folder: t.folder.clone(),
+                    }),
+                }
+            })
+            .collect();
+
+        Level {
+            ogmo_version: self.ogmo_version.clone(),
+            width,
+            height,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            values: self.default_level_values(),
+            layers,
+            extra: ValueMap::new(),
+        }
+    }
+
+    /// Renames a tileset, updating every reference to it in the project and the given
+    /// levels.
+    ///
+    /// This updates the tileset's label, the default tileset of any tile layer templates,
+    /// and the tileset of any tile or tile co-ords layers in the levels.
+    ///
+    /// Returns `false` (and makes no changes) if there is no tileset with the old label.
+    pub fn rename_tileset(&mut self, old: &str, new: &str, levels: &mut [Level]) -> bool {
+        match self.tileset_by_label_mut(old) {
+            Some(tileset) => tileset.label = new.to_owned(),
+            None => return false,
+        }
+
+        for template in &mut self.layers {
+            if let LayerTemplate::Tile(template) = template {
+                if template.default_tileset == old {
+                    template.default_tileset = new.to_owned();
+                }
+            }
+        }
+
+        for layer in levels.iter_mut().flat_map(|l| &mut l.layers) {
+            let tileset = match layer {
+                Layer::Tile(layer) => &mut layer.tileset,
+                Layer::TileCoords(layer) => &mut layer.tileset,
+                _ => continue,
+            };
+
+            if tileset == old {
+                *tileset = new.to_owned();
+            }
+        }
+
+        true
+    }
+
+    /// Parses the project's background color.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidColor` will be returned if the color is not a valid hex color.
+    pub fn background_color_parsed(&self) -> Result<Color, Error> {
+        Color::parse(&self.background_color)
+    }
+
+    /// Parses the project's grid color.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidColor` will be returned if the color is not a valid hex color.
+    pub fn grid_color_parsed(&self) -> Result<Color, Error> {
+        Color::parse(&self.grid_color)
+    }
+
+    /// Converts an angle from the project's units into radians.
+    ///
+    /// Entity and decal rotations are stored in radians or degrees, depending on the
+    /// project's `angles_radians` setting - this can be used to normalize them.
+    pub fn to_radians(&self, angle: f32) -> f32 {
+        if self.angles_radians {
+            angle
+        } else {
+            angle.to_radians()
+        }
+    }
+
+    /// Converts an angle from the project's units into degrees.
+    pub fn to_degrees(&self, angle: f32) -> f32 {
+        if self.angles_radians {
+            angle.to_degrees()
+        } else {
+            angle
+        }
+    }
+}
+
+/// A builder for creating a `Project` in code.
+///
+/// Any fields that are not set will use the same defaults as a new project in the Ogmo
+/// editor. As all of `Project`'s fields are public, anything that the builder doesn't
+/// cover can be changed after calling `build`.
+#[derive(Clone, Debug)]
+pub struct ProjectBuilder {
+    project: Project,
+}
+
+impl ProjectBuilder {
+    /// Creates a new builder, for a project with no layers, entities or tilesets.
+    pub fn new() -> ProjectBuilder {
+        ProjectBuilder {
+            project: Project {
+                name: "New Project".into(),
+                ogmo_version: "3.4.0".into(),
+                level_paths: vec![".".into()],
+                background_color: "#282c34ff".into(),
+                grid_color: "#3c4049cc".into(),
+                angles_radians: false,
+                directory_depth: 5,
+                layer_grid_default_size: Vec2 { x: 8, y: 8 },
+                level_default_size: Vec2 { x: 320, y: 240 },
+                level_min_size: Vec2 { x: 128, y: 128 },
+                level_max_size: Vec2 { x: 4096, y: 4096 },
+                level_values: Vec::new(),
+                default_export_mode: ".json".into(),
+                compact_export: false,
+                entity_tags: Vec::new(),
+                layers: Vec::new(),
+                entities: Vec::new(),
+                tilesets: Vec::new(),
+                levels: Vec::new(),
+                #[cfg(feature = "std")]
+                base_directory: None,
+                extra: ValueMap::new(),
+            },
+        }
+    }
+
+    /// Sets the name of the project.
+    pub fn name(mut self, name: impl Into<String>) -> ProjectBuilder {
+        self.project.name = name.into();
+        self
+    }
+
+    /// Sets the paths that hold the project's levels.
+    ///
+    /// Defaults to the directory containing the project.
+    pub fn level_paths(mut self, level_paths: Vec<String>) -> ProjectBuilder {
+        self.project.level_paths = level_paths;
+        self
+    }
+
+    /// Sets whether the project describes angles in radians or degrees.
+    ///
+    /// Defaults to degrees.
+    pub fn angles_radians(mut self, angles_radians: bool) -> ProjectBuilder {
+        self.project.angles_radians = angles_radians;
+        self
+    }
+
+    /// Sets the default grid size for newly created layers.
+    pub fn layer_grid_default_size(mut self, size: Vec2<i32>) -> ProjectBuilder {
+        self.project.layer_grid_default_size = size;
+        self
+    }
+
+    /// Sets the default size of newly created levels.
+    pub fn level_default_size(mut self, size: Vec2<i32>) -> ProjectBuilder {
+        self.project.level_default_size = size;
+        self
+    }
+
+    /// Sets the value templates for the project's levels.
+    pub fn level_values(mut self, level_values: Vec<ValueTemplate>) -> ProjectBuilder {
+        self.project.level_values = level_values;
+        self
+    }
+
+    /// Sets the tags that can be attached to entities.
+    pub fn entity_tags(mut self, entity_tags: Vec<String>) -> ProjectBuilder {
+        self.project.entity_tags = entity_tags;
+        self
+    }
+
+    /// Sets the project's layer templates.
+    pub fn layers(mut self, layers: Vec<LayerTemplate>) -> ProjectBuilder {
+        self.project.layers = layers;
+        self
+    }
+
+    /// Sets the project's entity templates.
+    pub fn entities(mut self, entities: Vec<EntityTemplate>) -> ProjectBuilder {
+        self.project.entities = entities;
+        self
+    }
+
+    /// Sets the project's tilesets.
+    pub fn tilesets(mut self, tilesets: Vec<Tileset>) -> ProjectBuilder {
+        self.project.tilesets = tilesets;
+        self
+    }
+
+    /// Creates the project.
+    pub fn build(self) -> Project {
+        self.project
+    }
+}
+
+impl Default for ProjectBuilder {
+    fn default() -> ProjectBuilder {
+        ProjectBuilder::new()
+    }
+}
+
+/// Gets the number of grid cells needed to cover the given size.
+pub(crate) fn cells_to_cover(size: f32, cell_size: i32) -> i32 {
+    if cell_size > 0 {
+        // `f32::ceil` is not available without `std`.
+        let cells = size / cell_size as f32;
+        let whole = cells as i32;
+
+        if (whole as f32) < cells {
+            whole + 1
+        } else {
+            whole.max(0)
+        }
+    } else {
+        0
+    }
+}
+
+/// Recursively collects the level files in a directory, searching at most `depth`
+/// levels of subdirectories.
+#[cfg(feature = "std")]
+fn find_level_files(dir: &Path, depth: i32, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            if depth > 0 {
+                find_level_files(&path, depth - 1, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
 }
 
 /// A template for a value.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(tag = "definition")]
 pub enum ValueTemplate {
     /// A boolean value template.
@@ -147,10 +904,371 @@ impl ValueTemplate {
             ValueTemplate::Text(data) => &data.name,
         }
     }
+
+    /// Gets the template's default value, in the form that it would be stored in a level.
+    ///
+    /// * Boolean templates produce a `Value::Boolean`.
+    /// * Integer templates produce a `Value::Integer`. This matches how whole numbers are
+    ///   parsed from a level, so the value will round trip without changing type.
+    /// * Enum templates produce a `Value::String` containing the label of the default
+    ///   choice, as that is how the editor stores enum values. If the default is not a
+    ///   valid choice, its index is returned as a `Value::Integer` instead (this will be
+    ///   reported by `Project::validate`).
+    /// * Float templates produce a `Value::Number`.
+    /// * Color, string and text templates produce a `Value::String`.
+    pub fn default_value(&self) -> Value {
+        match self {
+            ValueTemplate::Boolean(data) => Value::Boolean(data.defaults),
+            ValueTemplate::Color(data) => Value::String(data.defaults.clone()),
+            ValueTemplate::Enum(data) => usize::try_from(data.defaults)
+                .ok()
+                .and_then(|i| data.choices.get(i))
+                .map(|choice| Value::String(choice.clone()))
+                .unwrap_or(Value::Integer(i64::from(data.defaults))),
+            ValueTemplate::Integer(data) => Value::Integer(i64::from(data.defaults)),
+            ValueTemplate::Float(data) => Value::Number(data.defaults),
+            ValueTemplate::String(data) => Value::String(data.defaults.clone()),
+            ValueTemplate::Text(data) => Value::String(data.defaults.clone()),
+        }
+    }
+
+    /// Checks that a value from a level is valid for this template.
+    ///
+    /// As well as checking that the value has the right type (in the same way as
+    /// `resolve`), this checks numeric values against the template's bounds, enum
+    /// values against its choices, and strings against its maximum length.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not valid, an error describing the problem will be returned.
+    pub fn validate(&self, value: &Value) -> Result<(), ValidationError> {
+        let formatted = || json::to_string(value).unwrap_or_default();
+
+        let mismatched = || ValidationError::MismatchedValue {
+            template: self.name().to_owned(),
+            value: formatted(),
+        };
+
+        let out_of_bounds = |min: f64, max: f64| ValidationError::ValueOutOfBounds {
+            template: self.name().to_owned(),
+            value: formatted(),
+            min,
+            max,
+        };
+
+        match (self, self.resolve(value)) {
+            (ValueTemplate::Enum(data), Err(_)) => Err(ValidationError::InvalidEnumValue {
+                template: data.name.clone(),
+                value: formatted(),
+                choices: data.choices.len(),
+            }),
+
+            (_, Err(_)) => Err(mismatched()),
+
+            (ValueTemplate::Integer(data), Ok(TypedValue::Integer(n)))
+                if data.bounded && (n < data.min || n > data.max) =>
+            {
+                Err(out_of_bounds(f64::from(data.min), f64::from(data.max)))
+            }
+
+            (ValueTemplate::Float(data), Ok(TypedValue::Float(n)))
+                if data.bounded && (n < data.min || n > data.max) =>
+            {
+                Err(out_of_bounds(f64::from(data.min), f64::from(data.max)))
+            }
+
+            (ValueTemplate::String(data), Ok(TypedValue::String(s)))
+                if data.max_length > 0 && s.chars().count() > data.max_length as usize =>
+            {
+                Err(ValidationError::StringTooLong {
+                    template: data.name.clone(),
+                    length: s.chars().count(),
+                    max_length: data.max_length,
+                })
+            }
+
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolves a value from a level against this template, converting it to the
+    /// template's type.
+    ///
+    /// Whole numbers are accepted for float templates, and floats without a fractional
+    /// part are accepted for integer templates. Enum values can either be the label of
+    /// one of the template's choices, or the index of one.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if the value does not match the type
+    ///   of the template.
+    /// * `Error::InvalidColor` will be returned if a color value is not a valid hex color.
+    pub fn resolve(&self, value: &Value) -> Result<TypedValue, Error> {
+        let resolved = match (self, value) {
+            (ValueTemplate::Boolean(_), Value::Boolean(b)) => Some(TypedValue::Boolean(*b)),
+
+            (ValueTemplate::Color(_), Value::String(s)) => {
+                Some(TypedValue::Color(Color::parse(s)?))
+            }
+
+            (ValueTemplate::Enum(data), Value::String(s)) => data
+                .choices
+                .iter()
+                .find(|c| *c == s)
+                .map(|c| TypedValue::Enum(c.clone())),
+
+            (ValueTemplate::Enum(data), Value::Integer(i)) => usize::try_from(*i)
+                .ok()
+                .and_then(|i| data.choices.get(i))
+                .map(|c| TypedValue::Enum(c.clone())),
+
+            (ValueTemplate::Integer(_), Value::Integer(i)) => {
+                i32::try_from(*i).ok().map(TypedValue::Integer)
+            }
+
+            (ValueTemplate::Integer(_), Value::Number(n)) if is_whole(*n) => {
+                Some(TypedValue::Integer(*n as i32))
+            }
+
+            (ValueTemplate::Float(_), Value::Integer(i)) => Some(TypedValue::Float(*i as f32)),
+            (ValueTemplate::Float(_), Value::Number(n)) => Some(TypedValue::Float(*n)),
+            (ValueTemplate::String(_), Value::String(s)) => Some(TypedValue::String(s.clone())),
+            (ValueTemplate::Text(_), Value::String(s)) => Some(TypedValue::Text(s.clone())),
+
+            _ => None,
+        };
+
+        resolved.ok_or_else(|| {
+            Error::Inconsistent(format!(
+                "value {:?} does not match the type of value template '{}'",
+                value,
+                self.name()
+            ))
+        })
+    }
+
+    /// Gets the value template as a boolean value template, if it is one.
+    pub fn as_boolean(&self) -> Option<&BooleanValueTemplate> {
+        match self {
+            ValueTemplate::Boolean(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable boolean value template, if it is one.
+    pub fn as_boolean_mut(&mut self) -> Option<&mut BooleanValueTemplate> {
+        match self {
+            ValueTemplate::Boolean(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into a boolean value template, if it is one.
+    pub fn into_boolean(self) -> Option<BooleanValueTemplate> {
+        match self {
+            ValueTemplate::Boolean(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a color value template, if it is one.
+    pub fn as_color(&self) -> Option<&ColorValueTemplate> {
+        match self {
+            ValueTemplate::Color(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable color value template, if it is one.
+    pub fn as_color_mut(&mut self) -> Option<&mut ColorValueTemplate> {
+        match self {
+            ValueTemplate::Color(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into a color value template, if it is one.
+    pub fn into_color(self) -> Option<ColorValueTemplate> {
+        match self {
+            ValueTemplate::Color(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as an enum value template, if it is one.
+    pub fn as_enum(&self) -> Option<&EnumValueTemplate> {
+        match self {
+            ValueTemplate::Enum(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable enum value template, if it is one.
+    pub fn as_enum_mut(&mut self) -> Option<&mut EnumValueTemplate> {
+        match self {
+            ValueTemplate::Enum(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into an enum value template, if it is one.
+    pub fn into_enum(self) -> Option<EnumValueTemplate> {
+        match self {
+            ValueTemplate::Enum(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as an integer value template, if it is one.
+    pub fn as_integer(&self) -> Option<&IntegerValueTemplate> {
+        match self {
+            ValueTemplate::Integer(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable integer value template, if it is one.
+    pub fn as_integer_mut(&mut self) -> Option<&mut IntegerValueTemplate> {
+        match self {
+            ValueTemplate::Integer(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into an integer value template, if it is one.
+    pub fn into_integer(self) -> Option<IntegerValueTemplate> {
+        match self {
+            ValueTemplate::Integer(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a float value template, if it is one.
+    pub fn as_float(&self) -> Option<&FloatValueTemplate> {
+        match self {
+            ValueTemplate::Float(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable float value template, if it is one.
+    pub fn as_float_mut(&mut self) -> Option<&mut FloatValueTemplate> {
+        match self {
+            ValueTemplate::Float(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into a float value template, if it is one.
+    pub fn into_float(self) -> Option<FloatValueTemplate> {
+        match self {
+            ValueTemplate::Float(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a string value template, if it is one.
+    pub fn as_string(&self) -> Option<&StringValueTemplate> {
+        match self {
+            ValueTemplate::String(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable string value template, if it is one.
+    pub fn as_string_mut(&mut self) -> Option<&mut StringValueTemplate> {
+        match self {
+            ValueTemplate::String(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into a string value template, if it is one.
+    pub fn into_string(self) -> Option<StringValueTemplate> {
+        match self {
+            ValueTemplate::String(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a text value template, if it is one.
+    pub fn as_text(&self) -> Option<&TextValueTemplate> {
+        match self {
+            ValueTemplate::Text(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the value template as a mutable text value template, if it is one.
+    pub fn as_text_mut(&mut self) -> Option<&mut TextValueTemplate> {
+        match self {
+            ValueTemplate::Text(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the value template into a text value template, if it is one.
+    pub fn into_text(self) -> Option<TextValueTemplate> {
+        match self {
+            ValueTemplate::Text(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// Builds a map of custom values from the defaults of the given templates.
+fn default_values(templates: &[ValueTemplate]) -> ValueMap<String, Value> {
+    templates
+        .iter()
+        .map(|t| (t.name().to_owned(), t.default_value()))
+        .collect()
+}
+
+/// Resolves a map of custom values against the given templates.
+///
+/// Values that are missing from the map are filled in with the template's default.
+pub(crate) fn resolve_values(
+    values: &ValueMap<String, Value>,
+    templates: &[ValueTemplate],
+) -> Result<ValueMap<String, TypedValue>, Error> {
+    if let Some(name) = values
+        .keys()
+        .find(|name| templates.iter().all(|t| t.name() != name.as_str()))
+    {
+        return Err(Error::Inconsistent(format!(
+            "value '{}' does not match any value template",
+            name
+        )));
+    }
+
+    templates
+        .iter()
+        .map(|t| {
+            let value = match values.get(t.name()) {
+                Some(value) => t.resolve(value)?,
+                None => t.resolve(&t.default_value())?,
+            };
+
+            Ok((t.name().to_owned(), value))
+        })
+        .collect()
+}
+
+/// Checks a map of custom values against the given templates, returning any errors.
+///
+/// Values without a matching template are ignored.
+pub(crate) fn validate_values(
+    values: &ValueMap<String, Value>,
+    templates: &[ValueTemplate],
+) -> Vec<ValidationError> {
+    templates
+        .iter()
+        .filter_map(|t| t.validate(values.get(t.name())?).err())
+        .collect()
 }
 
 /// A boolean value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BooleanValueTemplate {
     /// The name of the value.
@@ -158,10 +1276,18 @@ pub struct BooleanValueTemplate {
 
     /// The default value.
     pub defaults: bool,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 /// A color value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ColorValueTemplate {
     /// The name of the value.
@@ -172,10 +1298,29 @@ pub struct ColorValueTemplate {
 
     /// Whether the alpha component will be included in the color.
     pub include_alpha: bool,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl ColorValueTemplate {
+    /// Parses the template's default color.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidColor` will be returned if the color is not a valid hex color.
+    pub fn defaults_parsed(&self) -> Result<Color, Error> {
+        Color::parse(&self.defaults)
+    }
 }
 
 /// An enum value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnumValueTemplate {
     /// The name of the value.
@@ -186,10 +1331,18 @@ pub struct EnumValueTemplate {
 
     /// The available choices for the enum.
     pub choices: Vec<String>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 /// An integer value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IntegerValueTemplate {
     /// The name of the value.
@@ -206,10 +1359,18 @@ pub struct IntegerValueTemplate {
 
     /// The maximum value.
     pub max: i32,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 /// A float value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FloatValueTemplate {
     /// The name of the value.
@@ -226,10 +1387,18 @@ pub struct FloatValueTemplate {
 
     /// The maximum value.
     pub max: f32,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 /// A string value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StringValueTemplate {
     /// The name of the value.
@@ -243,10 +1412,18 @@ pub struct StringValueTemplate {
 
     /// Whether whitespace should be trimmed from the beginning and end of the string.
     pub trim_whitespace: bool,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 /// A text value template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TextValueTemplate {
     /// The name of the value.
@@ -254,10 +1431,18 @@ pub struct TextValueTemplate {
 
     /// The default value.
     pub defaults: String,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
 }
 
 /// A template for a layer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum LayerTemplate {
     /// A tile layer template.
@@ -283,10 +1468,131 @@ impl LayerTemplate {
             LayerTemplate::Decal(data) => &data.name,
         }
     }
+
+    /// Gets the export ID of the layer template.
+    pub fn export_id(&self) -> &str {
+        match self {
+            LayerTemplate::Tile(data) => &data.export_id,
+            LayerTemplate::Grid(data) => &data.export_id,
+            LayerTemplate::Entity(data) => &data.export_id,
+            LayerTemplate::Decal(data) => &data.export_id,
+        }
+    }
+
+    /// Gets the layer template as a tile layer template, if it is one.
+    pub fn as_tile(&self) -> Option<&TileLayerTemplate> {
+        match self {
+            LayerTemplate::Tile(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as a mutable tile layer template, if it is one.
+    pub fn as_tile_mut(&mut self) -> Option<&mut TileLayerTemplate> {
+        match self {
+            LayerTemplate::Tile(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer template into a tile layer template, if it is one.
+    pub fn into_tile(self) -> Option<TileLayerTemplate> {
+        match self {
+            LayerTemplate::Tile(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as a grid layer template, if it is one.
+    pub fn as_grid(&self) -> Option<&GridLayerTemplate> {
+        match self {
+            LayerTemplate::Grid(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as a mutable grid layer template, if it is one.
+    pub fn as_grid_mut(&mut self) -> Option<&mut GridLayerTemplate> {
+        match self {
+            LayerTemplate::Grid(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer template into a grid layer template, if it is one.
+    pub fn into_grid(self) -> Option<GridLayerTemplate> {
+        match self {
+            LayerTemplate::Grid(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as an entity layer template, if it is one.
+    pub fn as_entity(&self) -> Option<&EntityLayerTemplate> {
+        match self {
+            LayerTemplate::Entity(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as a mutable entity layer template, if it is one.
+    pub fn as_entity_mut(&mut self) -> Option<&mut EntityLayerTemplate> {
+        match self {
+            LayerTemplate::Entity(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer template into an entity layer template, if it is one.
+    pub fn into_entity(self) -> Option<EntityLayerTemplate> {
+        match self {
+            LayerTemplate::Entity(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as a decal layer template, if it is one.
+    pub fn as_decal(&self) -> Option<&DecalLayerTemplate> {
+        match self {
+            LayerTemplate::Decal(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Gets the layer template as a mutable decal layer template, if it is one.
+    pub fn as_decal_mut(&mut self) -> Option<&mut DecalLayerTemplate> {
+        match self {
+            LayerTemplate::Decal(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the layer template into a decal layer template, if it is one.
+    pub fn into_decal(self) -> Option<DecalLayerTemplate> {
+        match self {
+            LayerTemplate::Decal(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// Deserializes the fields of a layer template that this crate does not recognize.
+///
+/// Serde passes the `definition` tag through to flattened fields, so it is removed here
+/// to avoid it being written out twice.
+fn deserialize_layer_template_extra<'de, D>(
+    deserializer: D,
+) -> Result<ValueMap<String, serde_json::Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut extra = ValueMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    crate::remove_entry(&mut extra, "definition");
+    Ok(extra)
 }
 
 /// A tile layer template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "definition", rename = "tile")]
 pub struct TileLayerTemplate {
@@ -308,10 +1614,33 @@ pub struct TileLayerTemplate {
 
     /// The default tileset for the layer.
     pub default_tileset: String,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted",
+        deserialize_with = "deserialize_layer_template_extra"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl Default for TileLayerTemplate {
+    fn default() -> TileLayerTemplate {
+        TileLayerTemplate {
+            name: String::new(),
+            grid_size: Vec2 { x: 8, y: 8 },
+            export_id: String::new(),
+            export_mode: ExportMode::Ids,
+            array_mode: ArrayMode::One,
+            default_tileset: String::new(),
+            extra: ValueMap::new(),
+        }
+    }
 }
 
 /// A grid layer template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "definition", rename = "grid")]
 pub struct GridLayerTemplate {
@@ -328,12 +1657,61 @@ pub struct GridLayerTemplate {
     /// Whether the tile data is stored as a 1D array or a 2D array.
     pub array_mode: ArrayMode,
 
-    /// Descriptions for the available grid cells.
-    pub legend: HashMap<String, String>,
+    /// Descriptions for the available grid cells, keyed by the value that is stored
+    /// in the level data.
+    ///
+    /// The editor describes each value with the color that it is drawn in. By default,
+    /// `"0"` means 'empty' and `"1"` means 'filled', but this is customizable in the
+    /// editor.
+    #[serde(serialize_with = "json::serialize_sorted")]
+    pub legend: ValueMap<String, String>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted",
+        deserialize_with = "deserialize_layer_template_extra"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl Default for GridLayerTemplate {
+    fn default() -> GridLayerTemplate {
+        GridLayerTemplate {
+            name: String::new(),
+            grid_size: Vec2 { x: 8, y: 8 },
+            export_id: String::new(),
+            array_mode: ArrayMode::One,
+            legend: [("0", "#00000000"), ("1", "#000000ff")]
+                .iter()
+                .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+                .collect(),
+            extra: ValueMap::new(),
+        }
+    }
+}
+
+impl GridLayerTemplate {
+    /// Gets the legend entry for a grid cell value.
+    ///
+    /// Returns `None` if the value is not present in the legend.
+    pub fn describe(&self, value: &str) -> Option<&str> {
+        self.legend.get(value).map(String::as_str)
+    }
+
+    /// Returns an iterator over the values in the template's legend, in sorted order.
+    ///
+    /// This includes the empty value (`"0"` by default), if it is in the legend.
+    pub fn legend_keys(&self) -> impl Iterator<Item = &str> {
+        let mut keys: Vec<&str> = self.legend.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        keys.into_iter()
+    }
 }
 
 /// An entity layer template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "definition", rename = "entity")]
 pub struct EntityLayerTemplate {
@@ -352,10 +1730,32 @@ pub struct EntityLayerTemplate {
 
     /// Tags that must not be present for an entity to be displayed on this layer.
     pub excluded_tags: Vec<String>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted",
+        deserialize_with = "deserialize_layer_template_extra"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl Default for EntityLayerTemplate {
+    fn default() -> EntityLayerTemplate {
+        EntityLayerTemplate {
+            name: String::new(),
+            grid_size: Vec2 { x: 8, y: 8 },
+            export_id: String::new(),
+            required_tags: Vec::new(),
+            excluded_tags: Vec::new(),
+            extra: ValueMap::new(),
+        }
+    }
 }
 
 /// A decal layer template.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "definition", rename = "decal")]
 pub struct DecalLayerTemplate {
@@ -370,7 +1770,7 @@ pub struct DecalLayerTemplate {
     pub export_id: String,
 
     /// The path to search for decal images, relative to the project
-    pub folder: PathBuf,
+    pub folder: String,
 
     /// Whether image sequences are included as available decals.
     pub include_image_sequence: bool,
@@ -383,13 +1783,39 @@ pub struct DecalLayerTemplate {
 
     /// Value templates associated with this decal layer.
     pub values: Vec<ValueTemplate>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted",
+        deserialize_with = "deserialize_layer_template_extra"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl Default for DecalLayerTemplate {
+    fn default() -> DecalLayerTemplate {
+        DecalLayerTemplate {
+            name: String::new(),
+            grid_size: Vec2 { x: 8, y: 8 },
+            export_id: String::new(),
+            folder: String::new(),
+            include_image_sequence: true,
+            scaleable: false,
+            rotatable: false,
+            values: Vec::new(),
+            extra: ValueMap::new(),
+        }
+    }
 }
 
 /// Defines whether tile data is stored as IDs or co-oords.
-#[derive(Clone, Debug, Deserialize_repr, Serialize_repr)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize_repr, Serialize_repr)]
 #[repr(u8)]
 pub enum ExportMode {
     /// The tile data is represented by IDs (counting left to right, top to bottom).
+    #[default]
     Ids = 0,
 
     /// The tile data is represented by co-ordinates.
@@ -397,10 +1823,11 @@ pub enum ExportMode {
 }
 
 /// Defines whether tile data is stored as a 1D array or a 2D array.
-#[derive(Clone, Debug, Deserialize_repr, Serialize_repr)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize_repr, Serialize_repr)]
 #[repr(u8)]
 pub enum ArrayMode {
     /// The tile data is stored in a 1D array.
+    #[default]
     One = 0,
 
     /// The tile data is stored in a 2D array.
@@ -408,7 +1835,7 @@ pub enum ArrayMode {
 }
 
 /// A template for an entity.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EntityTemplate {
     /// The name of the entity.
@@ -491,10 +1918,130 @@ pub struct EntityTemplate {
     /// The entity's texture, encoded in base 64.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub texture_image: Option<String>,
+
+    /// Fields that this crate does not recognize.
+    #[serde(
+        flatten,
+        skip_serializing_if = "ValueMap::is_empty",
+        serialize_with = "json::serialize_sorted"
+    )]
+    pub extra: ValueMap<String, serde_json::Value>,
+}
+
+impl Default for EntityTemplate {
+    fn default() -> EntityTemplate {
+        EntityTemplate {
+            name: String::new(),
+            export_id: String::new(),
+            limit: 0,
+            size: Vec2 { x: 16.0, y: 16.0 },
+            origin: Vec2 { x: 0.0, y: 0.0 },
+            origin_anchored: true,
+            shape: Shape::default(),
+            color: "#ff0000ff".into(),
+            tile_x: false,
+            tile_y: false,
+            tile_size: Vec2 { x: 16.0, y: 16.0 },
+            resizeable_x: false,
+            resizeable_y: false,
+            rotatable: false,
+            rotation_degrees: 360.0,
+            can_flip_x: false,
+            can_flip_y: false,
+            can_set_color: false,
+            has_nodes: false,
+            node_limit: 0,
+            node_display: 0,
+            node_ghost: true,
+            tags: Vec::new(),
+            values: Vec::new(),
+            texture: None,
+            texture_image: None,
+            extra: ValueMap::new(),
+        }
+    }
+}
+
+impl EntityTemplate {
+    /// Gets the default custom values for an instance of this entity.
+    pub fn default_values(&self) -> ValueMap<String, Value> {
+        default_values(&self.values)
+    }
+
+    /// Creates a new instance of this entity at the given position.
+    ///
+    /// Optional fields are only set if the template allows them to be edited, in the
+    /// same way as the editor:
+    ///
+    /// * `width` and `height` are set to the template's size if it is resizeable.
+    /// * `rotation` is set to `0.0` if it is rotatable.
+    /// * `flipped_x` and `flipped_y` are set to `false` if it can be flipped on that axis.
+    /// * `nodes` is set to an empty list if it has nodes.
+    /// * `values` is set to the template's default values, if it has any.
+    pub fn instantiate(&self, id: i32, position: Vec2<f32>) -> Entity {
+        let resizeable = self.resizeable_x || self.resizeable_y;
+
+        Entity {
+            name: self.name.clone(),
+            id,
+            export_id: self.export_id.clone(),
+            x: position.x,
+            y: position.y,
+            width: if resizeable { Some(self.size.x) } else { None },
+            height: if resizeable { Some(self.size.y) } else { None },
+            origin_x: None,
+            origin_y: None,
+            rotation: if self.rotatable { Some(0.0) } else { None },
+            flipped_x: if self.can_flip_x { Some(false) } else { None },
+            flipped_y: if self.can_flip_y { Some(false) } else { None },
+            nodes: if self.has_nodes {
+                Some(Vec::new())
+            } else {
+                None
+            },
+            values: if self.values.is_empty() {
+                None
+            } else {
+                Some(self.default_values())
+            },
+            extra: ValueMap::new(),
+        }
+    }
+
+    /// Parses the entity's color.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidColor` will be returned if the color is not a valid hex color.
+    pub fn color_parsed(&self) -> Result<Color, Error> {
+        Color::parse(&self.color)
+    }
+
+    /// Decodes the entity's embedded texture into the raw bytes of the image file.
+    ///
+    /// Returns `Ok(None)` if the entity does not have a texture.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Base64` will be returned if the texture is not valid base 64.
+    pub fn decode_texture_image(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.texture_image
+            .as_deref()
+            .map(base64::decode)
+            .transpose()
+    }
+
+    /// Embeds the raw bytes of a PNG image file as the entity's texture.
+    ///
+    /// The image is encoded as a base 64 data URL, in the same format as the editor.
+    /// This does not change the entity's `texture` path.
+    pub fn set_texture_image_bytes(&mut self, png: &[u8]) {
+        self.texture_image = Some(base64::encode_png(png));
+    }
 }
 
 /// An entity's shape.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Shape {
     /// The shape's label.
     pub label: String,
@@ -503,15 +2050,71 @@ pub struct Shape {
     pub points: Vec<Vec2<f32>>,
 }
 
+impl Default for Shape {
+    /// Creates the editor's default shape, a rectangle made from two triangles.
+    fn default() -> Shape {
+        let points = [
+            (-1.0, -1.0),
+            (1.0, -1.0),
+            (-1.0, 1.0),
+            (1.0, -1.0),
+            (-1.0, 1.0),
+            (1.0, 1.0),
+        ];
+
+        Shape {
+            label: "Rectangle".into(),
+            points: points.iter().map(|&p| Vec2::from(p)).collect(),
+        }
+    }
+}
+
+impl Shape {
+    /// Checks whether a point falls within the shape.
+    ///
+    /// Ogmo stores shapes as a list of triangles (each set of three points making up
+    /// one triangle), so this checks each triangle in turn. Points on the edge of the
+    /// shape are considered to be inside it. If there are any trailing points that don't
+    /// form a full triangle, they are ignored.
+    pub fn contains_point(&self, point: Vec2<f32>) -> bool {
+        self.points.chunks_exact(3).any(|tri| {
+            let d1 = edge_side(point, tri[0], tri[1]);
+            let d2 = edge_side(point, tri[1], tri[2]);
+            let d3 = edge_side(point, tri[2], tri[0]);
+
+            let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+            let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+
+            !(has_neg && has_pos)
+        })
+    }
+
+    /// Calculates the area of the shape, by summing the area of its triangles.
+    ///
+    /// If there are any trailing points that don't form a full triangle, they are ignored.
+    pub fn area(&self) -> f32 {
+        self.points
+            .chunks_exact(3)
+            .map(|tri| (edge_side(tri[2], tri[0], tri[1]) / 2.0).abs())
+            .sum()
+    }
+}
+
+/// Returns the cross product of `b - a` and `p - a`, which indicates which side of
+/// the edge from `a` to `b` the point is on.
+fn edge_side(p: Vec2<f32>, a: Vec2<f32>, b: Vec2<f32>) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
 /// A tileset.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tileset {
     /// The name of the tileset.
     pub label: String,
 
     /// The path to the tileset's image, relative to the project's path.
-    pub path: PathBuf,
+    pub path: String,
 
     /// The tileset's image, encoded in base 64.
     pub image: String,
@@ -534,24 +2137,199 @@ impl Tileset {
     ///
     /// As the Ogmo project doesn't store the width and height of the texture (only the
     /// path to it), you must provide these values yourself.
+    ///
+    /// The tiles are yielded in order of their IDs, and as the number of tiles is known
+    /// up front, the iterator can also be reversed.
     pub fn tile_coords(
         &self,
         texture_width: i32,
         texture_height: i32,
-    ) -> impl Iterator<Item = Vec2<i32>> + '_ {
+    ) -> impl DoubleEndedIterator<Item = Vec2<i32>> + ExactSizeIterator + '_ {
+        self.tile_coords_with_margin(texture_width, texture_height, 0, 0)
+    }
+
+    /// Returns an iterator which yields the position of each tile in the tileset, where
+    /// the texture has a margin before the first tile.
+    ///
+    /// Ogmo doesn't support margins itself, but many tileset images have them - the
+    /// first tile will be at `(margin_x, margin_y)` rather than `(0, 0)`.
+    pub fn tile_coords_with_margin(
+        &self,
+        texture_width: i32,
+        texture_height: i32,
+        margin_x: i32,
+        margin_y: i32,
+    ) -> impl DoubleEndedIterator<Item = Vec2<i32>> + ExactSizeIterator + '_ {
         let step_x = self.tile_width + self.tile_separation_x;
         let step_y = self.tile_height + self.tile_separation_y;
 
-        let tiles_x = texture_width / step_x;
-        let tiles_y = texture_height / step_y;
+        let (tiles_x, tiles_y) =
+            self.tiles_per_axis(texture_width - margin_x, texture_height - margin_y);
 
-        (0..tiles_y).flat_map(move |tile_y| {
-            (0..tiles_x).map(move |tile_x| {
-                let x = tile_x * step_x;
-                let y = tile_y * step_y;
+        (0..tiles_x.max(0) * tiles_y.max(0)).map(move |i| {
+            let x = margin_x + (i % tiles_x) * step_x;
+            let y = margin_y + (i / tiles_x) * step_y;
 
-                Vec2 { x, y }
-            })
+            Vec2 { x, y }
         })
     }
+
+    /// Checks that the tileset's tile size and separation will slice a texture of the given
+    /// size into a whole number of tiles.
+    ///
+    /// This follows the same slicing rules as `tile_coords` - each tile is followed by its
+    /// separation, and any pixels that don't fit into a full tile are ignored.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Inconsistent` will be returned if the tile size is not positive, if the
+    ///   separation is negative, or if slicing the texture would leave pixels over.
+    pub fn validate_against(&self, texture_width: i32, texture_height: i32) -> Result<(), Error> {
+        if self.tile_width <= 0 || self.tile_height <= 0 {
+            return Err(Error::Inconsistent(format!(
+                "tileset '{}' has an invalid tile size: {}x{}",
+                self.label, self.tile_width, self.tile_height
+            )));
+        }
+
+        if self.tile_separation_x < 0 || self.tile_separation_y < 0 {
+            return Err(Error::Inconsistent(format!(
+                "tileset '{}' has a negative separation: {}x{}",
+                self.label, self.tile_separation_x, self.tile_separation_y
+            )));
+        }
+
+        let leftover_x = texture_width % (self.tile_width + self.tile_separation_x);
+        let leftover_y = texture_height % (self.tile_height + self.tile_separation_y);
+
+        if leftover_x != 0 || leftover_y != 0 {
+            return Err(Error::Inconsistent(format!(
+                "tileset '{}' does not evenly divide a {}x{} texture ({}x{} pixels left over)",
+                self.label, texture_width, texture_height, leftover_x, leftover_y
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the tileset's embedded image into the raw bytes of the image file.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::Base64` will be returned if the image is not valid base 64.
+    pub fn decode_image(&self) -> Result<Vec<u8>, Error> {
+        base64::decode(&self.image)
+    }
+
+    /// Embeds the raw bytes of a PNG image file as the tileset's image.
+    ///
+    /// The image is encoded as a base 64 data URL, in the same format as the editor.
+    /// This does not change the tileset's `path`.
+    pub fn set_image_bytes(&mut self, png: &[u8]) {
+        self.image = base64::encode_png(png);
+    }
+
+    /// Returns the number of tiles in the tileset, given the size of its texture.
+    pub fn tile_count(&self, texture_width: i32, texture_height: i32) -> i32 {
+        let (tiles_x, tiles_y) = self.tiles_per_axis(texture_width, texture_height);
+        tiles_x * tiles_y
+    }
+
+    /// Gets the area of the texture that contains the tile with the given ID, in pixels,
+    /// taking the tileset's separation into account.
+    ///
+    /// Returns `None` if the ID is out of range.
+    pub fn tile_rect(&self, id: i32, texture_width: i32, texture_height: i32) -> Option<Rect<i32>> {
+        let (tiles_x, tiles_y) = self.tiles_per_axis(texture_width, texture_height);
+
+        if id < 0 || id >= tiles_x * tiles_y {
+            return None;
+        }
+
+        Some(Rect {
+            x: (id % tiles_x) * (self.tile_width + self.tile_separation_x),
+            y: (id / tiles_x) * (self.tile_height + self.tile_separation_y),
+            width: self.tile_width,
+            height: self.tile_height,
+        })
+    }
+
+    fn tiles_per_axis(&self, texture_width: i32, texture_height: i32) -> (i32, i32) {
+        let step_x = self.tile_width + self.tile_separation_x;
+        let step_y = self.tile_height + self.tile_separation_y;
+
+        if step_x <= 0 || step_y <= 0 {
+            return (0, 0);
+        }
+
+        (texture_width / step_x, texture_height / step_y)
+    }
+}
+
+/// A borrowed view of a tileset, combined with the size of its texture.
+///
+/// As the Ogmo project doesn't store the size of a tileset's texture, most tile
+/// lookups need it to be passed in - this type allows it to be provided once, and
+/// is cheap to create and pass around.
+#[derive(Copy, Clone, Debug)]
+pub struct TilesetView<'a> {
+    /// The tileset.
+    pub tileset: &'a Tileset,
+
+    /// The size of the tileset's texture, in pixels.
+    pub texture_size: Vec2<i32>,
+}
+
+impl<'a> TilesetView<'a> {
+    /// Creates a new view of a tileset.
+    pub fn new(tileset: &'a Tileset, texture_size: Vec2<i32>) -> TilesetView<'a> {
+        TilesetView {
+            tileset,
+            texture_size,
+        }
+    }
+
+    /// Returns the number of tiles in the tileset.
+    pub fn tile_count(&self) -> i32 {
+        self.tileset
+            .tile_count(self.texture_size.x, self.texture_size.y)
+    }
+
+    /// Returns an iterator which yields the position of each tile in the tileset.
+    pub fn tile_coords(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Vec2<i32>> + ExactSizeIterator + 'a {
+        self.tileset
+            .tile_coords(self.texture_size.x, self.texture_size.y)
+    }
+
+    /// Gets the area of the texture that contains the tile with the given ID, in pixels.
+    ///
+    /// Returns `None` if the ID is out of range.
+    pub fn tile_rect(&self, id: i32) -> Option<Rect<i32>> {
+        self.tileset
+            .tile_rect(id, self.texture_size.x, self.texture_size.y)
+    }
+
+    /// Gets the texture co-ordinates of the top left and bottom right corners of the
+    /// tile with the given ID, normalized to the range `0.0` to `1.0`.
+    ///
+    /// Returns `None` if the ID is out of range.
+    pub fn tile_uvs(&self, id: i32) -> Option<(Vec2<f32>, Vec2<f32>)> {
+        let rect = self.tile_rect(id)?;
+
+        let width = self.texture_size.x as f32;
+        let height = self.texture_size.y as f32;
+
+        Some((
+            Vec2 {
+                x: rect.x as f32 / width,
+                y: rect.y as f32 / height,
+            },
+            Vec2 {
+                x: (rect.x + rect.width) as f32 / width,
+                y: (rect.y + rect.height) as f32 / height,
+            },
+        ))
+    }
 }
@@ -0,0 +1,99 @@
+use ogmo3::project::{
+    DecalLayerTemplate, EntityLayerTemplate, EntityTemplate, GridLayerTemplate,
+    IntegerValueTemplate, LayerTemplate, ProjectBuilder, TileLayerTemplate, ValueTemplate,
+};
+use ogmo3::{Project, Vec2};
+
+#[test]
+pub fn build_project() {
+    let sample = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    let project = ProjectBuilder::new()
+        .name("Built")
+        .level_paths(vec!["levels".to_owned()])
+        .angles_radians(true)
+        .level_default_size(Vec2 { x: 640, y: 480 })
+        .layers(sample.layers.clone())
+        .entities(sample.entities.clone())
+        .tilesets(sample.tilesets.clone())
+        .build();
+
+    assert_eq!(project.name, "Built");
+    assert_eq!(project.level_paths, vec!["levels".to_owned()]);
+    assert!(project.angles_radians);
+    assert_eq!(project.level_default_size, Vec2 { x: 640, y: 480 });
+    assert_eq!(project.layers.len(), sample.layers.len());
+    assert!(project.validate().is_ok());
+
+    let json = project.to_json().unwrap();
+    let parsed = Project::from_json(&json).unwrap();
+    assert_eq!(parsed.to_json().unwrap(), json);
+}
+
+#[test]
+pub fn default_project() {
+    let project = ProjectBuilder::default().build();
+
+    assert!(project.layers.is_empty());
+    assert!(project.entities.is_empty());
+    assert!(project.tilesets.is_empty());
+    assert!(!project.angles_radians);
+    assert!(project.background_color_parsed().is_ok());
+    assert!(project.grid_color_parsed().is_ok());
+}
+
+#[test]
+pub fn default_templates() {
+    let project = ProjectBuilder::new()
+        .layers(vec![
+            LayerTemplate::Tile(TileLayerTemplate {
+                name: "tiles".into(),
+                export_id: "1".into(),
+                ..Default::default()
+            }),
+            LayerTemplate::Grid(GridLayerTemplate {
+                name: "grid".into(),
+                export_id: "2".into(),
+                ..Default::default()
+            }),
+            LayerTemplate::Entity(EntityLayerTemplate {
+                name: "entities".into(),
+                export_id: "3".into(),
+                ..Default::default()
+            }),
+            LayerTemplate::Decal(DecalLayerTemplate {
+                name: "decals".into(),
+                export_id: "4".into(),
+                ..Default::default()
+            }),
+        ])
+        .entities(vec![EntityTemplate {
+            name: "player".into(),
+            export_id: "5".into(),
+            values: vec![ValueTemplate::Integer(IntegerValueTemplate {
+                name: "health".into(),
+                defaults: 3,
+                ..Default::default()
+            })],
+            ..Default::default()
+        }])
+        .build();
+
+    assert!(project.validate().is_ok());
+
+    let entity = &project.entities[0];
+    assert_eq!(entity.size, Vec2 { x: 16.0, y: 16.0 });
+    assert_eq!(entity.shape.area(), 4.0);
+    assert!(entity.shape.contains_point(Vec2 { x: 0.0, y: 0.0 }));
+
+    match &project.layers[1] {
+        LayerTemplate::Grid(grid) => assert_eq!(grid.describe("1"), Some("#000000ff")),
+        _ => panic!("expected a grid layer template"),
+    }
+
+    let level = project.new_level(32.0, 32.0);
+    assert_eq!(level.layers.len(), 4);
+
+    let json = project.to_json().unwrap();
+    Project::from_json(&json).unwrap();
+}
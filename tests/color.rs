@@ -0,0 +1,77 @@
+use ogmo3::{Color, Error, Project};
+
+#[test]
+pub fn parse_colors() {
+    let expected = Color {
+        r: 0xff,
+        g: 0x00,
+        b: 0x88,
+        a: 0xff,
+    };
+
+    assert_eq!(Color::parse("#f08").unwrap(), expected);
+    assert_eq!(Color::parse("#ff0088").unwrap(), expected);
+    assert_eq!(Color::parse("#FF0088ff").unwrap(), expected);
+
+    assert_eq!(Color::parse("#ff008880").unwrap().a, 0x80);
+}
+
+#[test]
+pub fn parse_invalid_colors() {
+    for s in &[
+        "",
+        "#",
+        "ff0088",
+        "#ff00",
+        "#ff00zz",
+        "#ff0088ff00",
+        "#+f0088",
+    ] {
+        assert!(
+            matches!(Color::parse(s), Err(Error::InvalidColor(v)) if v == *s),
+            "'{}' should not parse",
+            s
+        );
+    }
+}
+
+#[test]
+pub fn color_to_hex_string() {
+    let color = Color::parse("#0af").unwrap();
+    assert_eq!(color.to_hex_string(), "#00aaffff");
+    assert_eq!(Color::parse(&color.to_hex_string()).unwrap(), color);
+}
+
+#[test]
+pub fn parse_project_colors() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    assert_eq!(
+        project.background_color_parsed().unwrap(),
+        Color::parse(&project.background_color).unwrap()
+    );
+    assert_eq!(
+        project.grid_color_parsed().unwrap().to_hex_string(),
+        project.grid_color
+    );
+
+    for entity in &project.entities {
+        assert_eq!(entity.color_parsed().unwrap().to_hex_string(), entity.color);
+    }
+}
+
+#[test]
+pub fn parse_project_colors_without_alpha() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    project.background_color = "#282c34".into();
+    project.grid_color = "#3c4049cc".into();
+
+    assert_eq!(project.background_color_parsed().unwrap().a, 0xff);
+    assert_eq!(project.grid_color_parsed().unwrap().a, 0xcc);
+
+    let reparsed = Project::from_json(&project.to_json().unwrap()).unwrap();
+
+    assert_eq!(reparsed.background_color, "#282c34");
+    assert_eq!(reparsed.grid_color, "#3c4049cc");
+}
@@ -0,0 +1,346 @@
+use ogmo3::level::{
+    GridLayer, GridLayerStorage, TileCoordsLayerStorage, TileLayer, TileLayerStorage,
+};
+use ogmo3::project::ArrayMode;
+use ogmo3::{Error, Layer, Level, Project, Vec2};
+
+fn tile_layer(data: TileLayerStorage) -> TileLayer {
+    TileLayer {
+        name: "tiles".into(),
+        export_id: "0".into(),
+        offset_x: 8.0,
+        offset_y: 0.0,
+        grid_cell_width: 16,
+        grid_cell_height: 16,
+        grid_cells_x: 4,
+        grid_cells_y: 4,
+        tileset: "tileset".into(),
+        data,
+    }
+}
+
+fn grid_layer(data: GridLayerStorage) -> GridLayer {
+    GridLayer {
+        name: "grid".into(),
+        export_id: "0".into(),
+        offset_x: 0.0,
+        offset_y: 0.0,
+        grid_cell_width: 8,
+        grid_cell_height: 8,
+        grid_cells_x: 4,
+        grid_cells_y: 3,
+        data,
+    }
+}
+
+fn filled_tiles(layer: &TileLayer) -> Vec<(Option<i32>, Vec2<i32>)> {
+    layer
+        .unpack_with_offset()
+        .filter(|t| t.id.is_some())
+        .map(|t| (t.id, t.pixel_position))
+        .collect()
+}
+
+#[test]
+pub fn autocrop_tile_layer() {
+    #[rustfmt::skip]
+    let mut layer = tile_layer(TileLayerStorage::Data(vec![
+        -1, -1, -1, -1,
+        -1,  0,  1, -1,
+        -1, -1,  2, -1,
+        -1, -1, -1, -1,
+    ]));
+
+    let before = filled_tiles(&layer);
+    let (offset, size) = layer.autocrop();
+
+    assert_eq!(offset, Vec2 { x: 1, y: 1 });
+    assert_eq!(size, Vec2 { x: 2, y: 2 });
+    assert_eq!(layer.grid_cells_x, 2);
+    assert_eq!(layer.grid_cells_y, 2);
+    assert_eq!(layer.offset_x, 24.0);
+    assert_eq!(layer.offset_y, 16.0);
+    assert!(matches!(&layer.data, TileLayerStorage::Data(d) if d == &[0, 1, -1, 2]));
+    assert_eq!(filled_tiles(&layer), before);
+}
+
+#[test]
+pub fn autocrop_empty_layer() {
+    let mut layer = tile_layer(TileLayerStorage::Data2D(vec![vec![-1; 4]; 4]));
+
+    let (_, size) = layer.autocrop();
+
+    assert_eq!(size, Vec2 { x: 0, y: 0 });
+    assert_eq!(layer.unpack().count(), 0);
+}
+
+#[test]
+pub fn autocrop_grid_layer() {
+    let rows = [
+        ["0", "0", "0", "0"],
+        ["0", "0", "1", "1"],
+        ["0", "0", "0", "1"],
+    ];
+
+    let mut layer = grid_layer(GridLayerStorage::Grid2D(
+        rows.iter()
+            .map(|r| r.iter().map(|&v| v.to_owned()).collect())
+            .collect(),
+    ));
+
+    let (offset, size) = layer.autocrop();
+
+    assert_eq!(offset, Vec2 { x: 2, y: 1 });
+    assert_eq!(size, Vec2 { x: 2, y: 2 });
+    assert_eq!(layer.offset_x, 16.0);
+    assert_eq!(layer.offset_y, 8.0);
+
+    let cells: Vec<_> = layer
+        .unpack_with_offset()
+        .map(|c| (c.value, c.pixel_position))
+        .collect();
+
+    assert_eq!(
+        cells,
+        vec![
+            ("1", Vec2 { x: 16, y: 8 }),
+            ("1", Vec2 { x: 24, y: 8 }),
+            ("0", Vec2 { x: 16, y: 16 }),
+            ("1", Vec2 { x: 24, y: 16 }),
+        ]
+    );
+}
+
+#[test]
+pub fn set_tiles() {
+    let mut layer_1d = tile_layer(TileLayerStorage::Data(vec![-1; 16]));
+    let mut layer_2d = tile_layer(TileLayerStorage::Data2D(vec![vec![-1; 4]; 4]));
+
+    for layer in [&mut layer_1d, &mut layer_2d].iter_mut() {
+        assert!(layer.set(2, 1, Some(5)));
+        assert!(layer.set(3, 3, Some(1)));
+        assert!(layer.set(3, 3, None));
+        assert!(!layer.set(4, 0, Some(1)));
+        assert!(!layer.set(0, -1, Some(1)));
+
+        assert_eq!(layer.get(2, 1).unwrap().id, Some(5));
+        assert_eq!(layer.get(3, 3).unwrap().id, None);
+        assert_eq!(layer.unpack().filter(|t| t.id.is_some()).count(), 1);
+    }
+
+    // With i32 arithmetic, this position would wrap around to the first tile.
+    let mut layer = tile_layer(TileLayerStorage::Data(vec![-1; 16]));
+    layer.grid_cells_x = i32::MAX;
+    layer.grid_cells_y = i32::MAX;
+
+    assert!(!layer.set(2, 2, Some(1)));
+    assert!(matches!(&layer.data, TileLayerStorage::Data(d) if d.iter().all(|&v| v == -1)));
+}
+
+#[test]
+pub fn convert_storage() {
+    let flat = TileLayerStorage::Data(vec![0, 1, 2, 3, 4, 5]);
+
+    let rows = flat.to_2d(3);
+    assert!(matches!(&rows, TileLayerStorage::Data2D(d) if d == &[vec![0, 1, 2], vec![3, 4, 5]]));
+
+    let flat_again = rows.to_1d(3);
+    assert!(matches!(&flat_again, TileLayerStorage::Data(d) if d == &[0, 1, 2, 3, 4, 5]));
+
+    let grid = GridLayerStorage::Grid2D(vec![vec!["0".into(), "1".into()]]);
+    assert!(matches!(grid.to_1d(2), GridLayerStorage::Grid(d) if d == ["0", "1"]));
+
+    // Ragged rows are padded, so the cells after them stay in the same position.
+    let ragged = TileLayerStorage::Data2D(vec![vec![0, 1], vec![2, 3, 4]]);
+    assert!(matches!(ragged.to_1d(3), TileLayerStorage::Data(d) if d == [0, 1, -1, 2, 3, 4]));
+
+    let ragged = TileCoordsLayerStorage::DataCoords2D(vec![vec![vec![0, 0]], vec![vec![1, 0]]]);
+    assert!(matches!(
+        ragged.to_1d(2),
+        TileCoordsLayerStorage::DataCoords(d) if d == [vec![0, 0], vec![-1], vec![1, 0], vec![-1]]
+    ));
+
+    let ragged = GridLayerStorage::Grid2D(vec![vec!["1".into()], vec!["1".into(), "1".into()]]);
+    assert!(matches!(ragged.to_1d(2), GridLayerStorage::Grid(d) if d == ["1", "0", "1", "1"]));
+
+    // With no columns, there is nowhere to put the data.
+    assert!(matches!(flat.to_2d(0), TileLayerStorage::Data2D(d) if d.is_empty()));
+}
+
+#[test]
+pub fn subregion_quadrant() {
+    #[rustfmt::skip]
+    let layer = tile_layer(TileLayerStorage::Data2D(vec![
+        vec![ 0,  1,  2,  3],
+        vec![ 4,  5,  6,  7],
+        vec![ 8,  9, 10, 11],
+        vec![12, 13, 14, 15],
+    ]));
+
+    let quadrant = layer.subregion(Vec2 { x: 2, y: 2 }, Vec2 { x: 2, y: 2 });
+
+    assert_eq!(quadrant.grid_cells_x, 2);
+    assert_eq!(quadrant.grid_cells_y, 2);
+    assert_eq!(quadrant.offset_x, 40.0);
+    assert_eq!(quadrant.offset_y, 32.0);
+    assert!(
+        matches!(&quadrant.data, TileLayerStorage::Data2D(d) if d == &[vec![10, 11], vec![14, 15]])
+    );
+
+    for tile in quadrant.unpack_with_offset() {
+        let original = layer
+            .unpack_with_offset()
+            .find(|t| t.pixel_position == tile.pixel_position)
+            .unwrap();
+
+        assert_eq!(original.id, tile.id);
+    }
+
+    let overhanging = layer.subregion(Vec2 { x: 3, y: -1 }, Vec2 { x: 2, y: 2 });
+    assert!(
+        matches!(&overhanging.data, TileLayerStorage::Data2D(d) if d == &[vec![-1, -1], vec![3, -1]])
+    );
+}
+
+#[test]
+pub fn subregion_grid_layer() {
+    let layer = grid_layer(GridLayerStorage::Grid(
+        (0..12).map(|i| (i % 10).to_string()).collect(),
+    ));
+
+    let region = layer.subregion(Vec2 { x: 1, y: 1 }, Vec2 { x: 2, y: 2 });
+
+    assert_eq!(region.offset_x, 8.0);
+    assert_eq!(region.offset_y, 8.0);
+    assert!(matches!(&region.data, GridLayerStorage::Grid(d) if d == &["5", "6", "9", "0"]));
+}
+
+#[test]
+pub fn set_storage_mode() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let original =
+        level.to_tensor(&Project::from_file("./examples/sample_project/test.ogmo").unwrap());
+
+    level.set_storage_mode(ArrayMode::One);
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) => assert!(matches!(layer.data, TileLayerStorage::Data(_))),
+            Layer::TileCoords(layer) => {
+                assert!(matches!(layer.data, TileCoordsLayerStorage::DataCoords(_)))
+            }
+            Layer::Grid(layer) => assert!(matches!(layer.data, GridLayerStorage::Grid(_))),
+            _ => {}
+        }
+    }
+
+    level.set_storage_mode(ArrayMode::Two);
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) => {
+                assert!(
+                    matches!(&layer.data, TileLayerStorage::Data2D(d) if d.len() == layer.grid_cells_y as usize)
+                )
+            }
+            Layer::TileCoords(layer) => {
+                assert!(matches!(
+                    layer.data,
+                    TileCoordsLayerStorage::DataCoords2D(_)
+                ))
+            }
+            Layer::Grid(layer) => {
+                assert!(
+                    matches!(&layer.data, GridLayerStorage::Grid2D(d) if d.len() == layer.grid_cells_y as usize)
+                )
+            }
+            _ => {}
+        }
+    }
+
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    assert_eq!(level.to_tensor(&project), original);
+}
+
+#[test]
+pub fn content_eq_ignores_storage() {
+    let flat = tile_layer(TileLayerStorage::Data((0..16).collect()));
+    let rows = tile_layer(flat.data.to_2d(4));
+    assert!(flat.content_eq(&rows));
+
+    let mut changed = rows.clone();
+    changed.set(3, 3, None);
+    assert!(!flat.content_eq(&changed));
+
+    #[rustfmt::skip]
+    let grid = grid_layer(GridLayerStorage::Grid2D(vec![
+        vec!["1".into(), "0".into(), "0".into(), "0".into()],
+        vec!["0".into(), "1".into()],
+        vec!["0".into(), "0".into(), "1".into(), "0".into()],
+    ]));
+
+    let mut padded = vec!["0".to_string(); 12];
+    padded[0] = "1".into();
+    padded[5] = "1".into();
+    padded[10] = "1".into();
+
+    let flat_grid = grid_layer(GridLayerStorage::Grid(padded));
+    assert!(grid.content_eq(&flat_grid));
+
+    let mut smaller = flat_grid.clone();
+    smaller.grid_cells_y = 2;
+    assert!(!grid.content_eq(&smaller));
+}
+
+#[test]
+pub fn merge_chunked_levels() {
+    let left = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let mut right = left.clone();
+    right.offset_x = left.width;
+
+    let merged = Level::merged(&[left.clone(), right]).unwrap();
+
+    assert_eq!(merged.width, left.width * 2.0);
+    assert_eq!(merged.height, left.height);
+    assert_eq!(merged.layers.len(), left.layers.len());
+
+    let (original, combined) = match (&left.layers[0], &merged.layers[0]) {
+        (Layer::Tile(original), Layer::Tile(combined)) => (original, combined),
+        _ => panic!("expected tile layers"),
+    };
+
+    assert_eq!(combined.grid_cells_x, original.grid_cells_x * 2);
+    assert_eq!(combined.grid_cells_y, original.grid_cells_y);
+    assert!(matches!(combined.data, TileLayerStorage::Data(_)));
+
+    for tile in original.unpack() {
+        let (x, y) = (tile.grid_position.x, tile.grid_position.y);
+
+        assert_eq!(combined.get(x, y).unwrap().id, tile.id);
+        assert_eq!(
+            combined.get(x + original.grid_cells_x, y).unwrap().id,
+            tile.id
+        );
+    }
+
+    assert_eq!(merged.entities().count(), left.entities().count() * 2);
+
+    let decals: Vec<_> = merged.decals().map(|d| (d.x, d.y)).collect();
+    assert_eq!(decals, vec![(16.0, 232.0), (16.0 + left.width, 232.0)]);
+}
+
+#[test]
+pub fn merge_misaligned_levels() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let mut shifted = level.clone();
+    shifted.offset_x = 8.0;
+
+    assert!(matches!(
+        Level::merged(&[level, shifted]),
+        Err(Error::Inconsistent(_))
+    ));
+
+    assert!(matches!(Level::merged(&[]), Err(Error::Inconsistent(_))));
+}
@@ -0,0 +1,81 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use ogmo3::{Error, Project};
+
+fn temp_project_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ogmo3-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("levels/nested")).unwrap();
+    dir
+}
+
+#[test]
+pub fn levels_modified_since() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let dir = temp_project_dir("modified");
+
+    let level = fs::read("./examples/sample_project/levels/uno.json").unwrap();
+    fs::write(dir.join("levels/old.json"), &level).unwrap();
+    fs::write(dir.join("levels/nested/new.json"), &level).unwrap();
+    fs::write(dir.join("levels/notes.txt"), "not a level").unwrap();
+
+    File::options()
+        .write(true)
+        .open(dir.join("levels/old.json"))
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1000))
+        .unwrap();
+
+    let since = SystemTime::now() - Duration::from_secs(60);
+    let modified = project.levels_modified_since(&dir, since).unwrap();
+
+    assert_eq!(modified, vec![dir.join("./levels/nested/new.json")]);
+
+    let all = project
+        .levels_modified_since(&dir, SystemTime::UNIX_EPOCH)
+        .unwrap();
+
+    assert_eq!(all.len(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+pub fn load_levels() {
+    let dir = temp_project_dir("load-levels");
+
+    let level = fs::read("./examples/sample_project/levels/uno.json").unwrap();
+    fs::write(dir.join("levels/a.json"), &level).unwrap();
+    fs::write(dir.join("levels/nested/b.json"), &level).unwrap();
+    fs::write(dir.join("levels/notes.txt"), "not a level").unwrap();
+
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    project.level_paths = vec!["levels".to_owned()];
+
+    let levels = project.load_levels(&dir).unwrap();
+    let paths: Vec<_> = levels.iter().map(|(path, _)| path.clone()).collect();
+
+    assert_eq!(
+        paths,
+        vec![dir.join("levels/a.json"), dir.join("levels/nested/b.json")]
+    );
+
+    assert_eq!(levels[0].1.width, 320.0);
+
+    project.directory_depth = 0;
+    assert_eq!(project.load_levels(&dir).unwrap().len(), 1);
+
+    fs::write(dir.join("levels/broken.json"), "{").unwrap();
+
+    match project.load_levels(&dir) {
+        Err(Error::Level(path, cause)) => {
+            assert_eq!(path, dir.join("levels/broken.json"));
+            assert!(matches!(*cause, Error::Json(_)));
+        }
+        other => panic!("expected a level error, got {:?}", other),
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
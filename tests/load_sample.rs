@@ -1,4 +1,7 @@
-use ogmo3::{Level, Project};
+use std::fs;
+use std::path::PathBuf;
+
+use ogmo3::{Error, Layer, Level, Project, Value};
 
 #[test]
 pub fn load_sample_project() {
@@ -9,3 +12,290 @@ pub fn load_sample_project() {
 pub fn load_sample_level() {
     Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
 }
+
+#[test]
+pub fn load_from_slice() {
+    let project = fs::read("./examples/sample_project/test.ogmo").unwrap();
+    let project = Project::from_slice(&project).unwrap();
+    assert_eq!(project.base_directory, None);
+
+    let level = fs::read("./examples/sample_project/levels/uno.json").unwrap();
+    Level::from_slice(&level).unwrap();
+
+    assert!(matches!(Level::from_slice(b"{"), Err(Error::Json(_))));
+
+    let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+    with_bom.extend_from_slice(&level);
+    Level::from_slice(&with_bom).unwrap();
+}
+
+#[test]
+#[cfg(feature = "tokio")]
+pub fn load_files_async() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let project = Project::from_file_async("./examples/sample_project/test.ogmo")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            project.base_directory,
+            Some(PathBuf::from("./examples/sample_project"))
+        );
+
+        let level = Level::from_file_async(project.resolve_level_path(0).join("levels/uno.json"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            level,
+            Level::from_file("./examples/sample_project/levels/uno.json").unwrap()
+        );
+
+        assert!(matches!(
+            Level::from_file_async("./examples/sample_project/missing.json").await,
+            Err(Error::Io(_))
+        ));
+    });
+}
+
+#[test]
+pub fn load_files_with_bom() {
+    let dir = std::env::temp_dir().join(format!("ogmo3-bom-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    for (src, dest) in &[
+        ("./examples/sample_project/test.ogmo", "test.ogmo"),
+        ("./examples/sample_project/levels/uno.json", "uno.json"),
+    ] {
+        let json = fs::read_to_string(src).unwrap();
+        fs::write(dir.join(dest), format!("\u{feff}{}", json)).unwrap();
+    }
+
+    let project = Project::from_file(dir.join("test.ogmo")).unwrap();
+    assert_eq!(project.base_directory, Some(dir.clone()));
+
+    let level = fs::read_to_string(dir.join("uno.json")).unwrap();
+    assert!(level.starts_with('\u{feff}'));
+
+    Level::from_json(&level).unwrap();
+    Level::from_file(dir.join("uno.json")).unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+pub fn load_layer_without_tileset() {
+    for tileset in &[r#""tileset": "","#, r#""tileset": null,"#, ""] {
+        let json = format!(
+            r#"{{
+                "ogmoVersion": "3.2.2",
+                "width": 32,
+                "height": 16,
+                "layers": [{{
+                    "name": "tiles",
+                    "_eid": "1",
+                    "gridCellWidth": 16,
+                    "gridCellHeight": 16,
+                    "gridCellsX": 2,
+                    "gridCellsY": 1,
+                    {}
+                    "data": [-1, 0],
+                    "exportMode": 0,
+                    "arrayMode": 0
+                }}]
+            }}"#,
+            tileset
+        );
+
+        let level = Level::from_json(&json).unwrap();
+
+        match &level.layers[0] {
+            Layer::Tile(layer) => assert_eq!(layer.tileset_name(), None),
+            _ => panic!("expected a tile layer"),
+        }
+    }
+}
+
+#[test]
+pub fn load_decal_without_values() {
+    let json = r#"{
+        "ogmoVersion": "3.2.2",
+        "width": 32,
+        "height": 16,
+        "layers": [{
+            "name": "decals",
+            "_eid": "1",
+            "offsetX": 0,
+            "offsetY": 0,
+            "gridCellWidth": 16,
+            "gridCellHeight": 16,
+            "gridCellsX": 2,
+            "gridCellsY": 1,
+            "decals": [{ "x": 8, "y": 8, "texture": "tiles.png" }],
+            "folder": "img"
+        }]
+    }"#;
+
+    let level = Level::from_json(json).unwrap();
+
+    match &level.layers[0] {
+        Layer::Decal(layer) => assert!(layer.decals[0].values.is_empty()),
+        _ => panic!("expected a decal layer"),
+    }
+
+    assert!(level
+        .to_json()
+        .unwrap()
+        .contains(r#"{"x":8,"y":8,"texture":"tiles.png"}"#));
+}
+
+#[test]
+pub fn invalid_layer_error() {
+    let json = r#"{
+        "ogmoVersion": "3.2.2",
+        "width": 32,
+        "height": 16,
+        "layers": [
+            { "name": "entities", "_eid": "1", "gridCellWidth": 16, "gridCellHeight": 16, "gridCellsX": 2, "gridCellsY": 1, "entities": [] },
+            { "name": "broken", "_eid": "2", "gridCellWidth": 16, "data": [] }
+        ]
+    }"#;
+
+    let error = match Level::from_json(json) {
+        Err(Error::Json(e)) => e,
+        _ => panic!("expected a JSON error"),
+    };
+
+    let message = error.to_string();
+
+    assert!(
+        message.contains("invalid layer 1 ('broken')"),
+        "{}",
+        message
+    );
+
+    // The position is the point that the parser had reached when the error was
+    // reported, which may be just after the broken layer.
+    assert!(error.line() >= 7, "{}", message);
+    assert_eq!(message.matches(" at line ").count(), 1, "{}", message);
+
+    let json = json.replace(r#""data": []"#, r#""data": [0, "1"]"#);
+
+    let error = match Level::from_json(&json) {
+        Err(Error::Json(e)) => e,
+        _ => panic!("expected a JSON error"),
+    };
+
+    let message = error.to_string();
+
+    assert!(
+        message.contains("invalid layer 1 ('broken'): invalid type: string \"1\""),
+        "{}",
+        message
+    );
+
+    assert!(error.line() >= 7, "{}", message);
+    assert_eq!(message.matches(" at line ").count(), 1, "{}", message);
+}
+
+#[test]
+pub fn error_display_includes_cause() {
+    let error = Level::from_file("./examples/sample_project/levels/missing.json").unwrap_err();
+    let message = error.to_string();
+
+    assert!(message.starts_with("IO error: "), "{}", message);
+    assert!(message.len() > "IO error: ".len());
+}
+
+#[test]
+pub fn load_embedded_levels() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    assert!(project.embedded_levels().is_empty());
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("./examples/sample_project/test.ogmo").unwrap())
+            .unwrap();
+
+    json["levels"] = serde_json::json!([serde_json::from_str::<serde_json::Value>(
+        &fs::read_to_string("./examples/sample_project/levels/uno.json").unwrap()
+    )
+    .unwrap()]);
+
+    let project = Project::from_json(&json.to_string()).unwrap();
+    let levels = project.embedded_levels();
+
+    assert_eq!(levels.len(), 1);
+    assert_eq!(levels[0].width, 320.0);
+    assert_eq!(levels[0].layers.len(), 8);
+}
+
+#[test]
+pub fn new_level_from_project() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = project.new_level(100.0, 40.0);
+
+    assert_eq!(level.layers.len(), project.layers.len());
+    assert!(matches!(&level.values["poop"], Value::String(s) if s == "#000000ff"));
+    assert!(level.validate(&project).is_ok());
+
+    for (layer, template) in level.layers.iter().zip(&project.layers) {
+        assert_eq!(layer.name(), template.name());
+        assert_eq!(layer.export_id(), template.export_id());
+
+        match layer {
+            Layer::Tile(layer) => {
+                assert_eq!((layer.grid_cells_x, layer.grid_cells_y), (7, 3));
+                assert!(layer.unpack().all(|t| t.id.is_none()));
+                assert_eq!(layer.unpack().count(), 21);
+            }
+            Layer::TileCoords(layer) => {
+                assert!(layer.unpack().all(|t| t.grid_coords.is_none()));
+                assert_eq!(layer.unpack().count(), 21);
+            }
+            Layer::Grid(layer) => {
+                assert!(layer.unpack().all(|c| c.value == "0"));
+                assert_eq!(
+                    layer.unpack().count() as i32,
+                    layer.grid_cells_x * layer.grid_cells_y
+                );
+            }
+            Layer::Entity(layer) => assert!(layer.entities.is_empty()),
+            Layer::Decal(layer) => assert!(layer.decals.is_empty()),
+            Layer::Unknown(raw) => panic!("layer should have a known type: {}", raw),
+        }
+    }
+
+    // The level should survive a round trip, including the storage modes.
+    let json = level.to_json().unwrap();
+    let reparsed = Level::from_json(&json).unwrap();
+    assert_eq!(reparsed.to_json().unwrap(), json);
+}
+
+#[test]
+pub fn resolve_level_paths() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    assert_eq!(
+        project.base_directory,
+        Some(PathBuf::from("./examples/sample_project"))
+    );
+
+    assert_eq!(
+        project.resolve_level_path(0),
+        PathBuf::from("./examples/sample_project/.")
+    );
+
+    assert_eq!(
+        project.resolved_level_paths().collect::<Vec<_>>(),
+        vec![PathBuf::from("./examples/sample_project/.")]
+    );
+
+    let project = Project::from_json(&project.to_json().unwrap()).unwrap();
+
+    assert_eq!(project.base_directory, None);
+    assert_eq!(project.resolve_level_path(0), PathBuf::from("."));
+}
@@ -0,0 +1,554 @@
+use ogmo3::project::LayerTemplate;
+use ogmo3::{Layer, Level, Project, Vec2};
+
+#[test]
+pub fn max_tile_id() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    assert_eq!(level.max_tile_id("New Tileset"), Some(1));
+    assert_eq!(level.max_tile_id("Missing Tileset"), None);
+}
+
+#[test]
+pub fn get_cells() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) if layer.name == "tile_1D" => {
+                let tile = layer.get(1, 0).unwrap();
+                assert_eq!(tile.id, Some(1));
+                assert_eq!(tile.pixel_position, Vec2 { x: 16, y: 0 });
+
+                assert_eq!(layer.get(2, 0).unwrap().id, None);
+                assert!(layer.get(20, 0).is_none());
+                assert!(layer.get(-1, 0).is_none());
+            }
+
+            Layer::Tile(layer) if layer.name == "tile_2D" => {
+                assert_eq!(layer.get(1, 1).unwrap().id, Some(1));
+                assert_eq!(layer.get(0, 1).unwrap().id, None);
+                assert!(layer.get(0, 15).is_none());
+            }
+
+            Layer::TileCoords(layer) => {
+                assert!(layer.get(0, 0).is_some());
+                assert!(layer.get(20, 15).is_none());
+            }
+
+            Layer::Grid(layer) => {
+                assert_eq!(layer.get(0, 0).unwrap().value, "0");
+                assert!(layer.get(layer.grid_cells_x, 0).is_none());
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[test]
+pub fn get_cells_with_large_dimensions() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) if layer.name == "tile_1D" => {
+                // With i32 arithmetic, this position would wrap around to the first tile.
+                let mut huge = layer.clone();
+                huge.grid_cells_x = i32::MAX;
+                huge.grid_cells_y = i32::MAX;
+                assert!(huge.get(2, 2).is_none());
+
+                let mut wide = layer.clone();
+                wide.grid_cell_width = i32::MAX;
+                assert!(wide.get(0, 0).is_some());
+                assert!(wide.get(2, 0).is_none());
+            }
+
+            Layer::TileCoords(layer) if layer.name == "tileCoords_1D" => {
+                let mut huge = layer.clone();
+                huge.grid_cells_x = i32::MAX;
+                huge.grid_cells_y = i32::MAX;
+                assert!(huge.get(2, 2).is_none());
+            }
+
+            Layer::Grid(layer) if layer.name == "grid_1D" => {
+                let mut huge = layer.clone();
+                huge.grid_cells_x = i32::MAX;
+                huge.grid_cells_y = i32::MAX;
+                assert!(huge.get(2, 2).is_none());
+
+                let mut tall = layer.clone();
+                tall.grid_cell_height = i32::MAX;
+                assert!(tall.get(0, 2).is_none());
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[test]
+pub fn describe_grid_cells() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let template = project
+        .layers
+        .iter()
+        .find_map(|t| match t {
+            LayerTemplate::Grid(t) if t.name == "grid_1D" => Some(t),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(template.describe("a"), Some("#000000ff"));
+    assert_eq!(template.describe("z"), None);
+    assert_eq!(template.legend_keys().collect::<Vec<_>>(), ["0", "3", "a"]);
+
+    let layer = level
+        .layers
+        .iter()
+        .find_map(|l| match l {
+            Layer::Grid(l) if l.name == "grid_1D" => Some(l),
+            _ => None,
+        })
+        .unwrap();
+
+    for cell in layer.unpack() {
+        assert_eq!(
+            cell.describe(template),
+            template.legend.get(cell.value).map(|s| s.as_str())
+        );
+    }
+}
+
+#[test]
+pub fn entity_template_lookup() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        if let Layer::Entity(layer) = layer {
+            for entity in &layer.entities {
+                let by_name = project.entity_template_by_name(&entity.name).unwrap();
+                let by_eid = project
+                    .entity_template_by_export_id(&entity.export_id)
+                    .unwrap();
+
+                assert_eq!(by_name.name, by_eid.name);
+            }
+        }
+    }
+
+    assert!(project.entity_template_by_name("Missing").is_none());
+    assert!(project.entity_template_by_export_id("0").is_none());
+}
+
+#[test]
+pub fn layer_template_lookup() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        let by_eid = project
+            .layer_template_by_export_id(layer.export_id())
+            .unwrap();
+
+        assert_eq!(by_eid.name(), layer.name());
+        assert_eq!(by_eid.export_id(), layer.export_id());
+
+        let by_name = project.layer_template_by_name(layer.name()).unwrap();
+        assert_eq!(by_name.export_id(), layer.export_id());
+    }
+
+    assert!(project.layer_template_by_name("Missing").is_none());
+    assert!(project.layer_template_by_export_id("0").is_none());
+}
+
+#[test]
+pub fn layer_coverage() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut level = project.new_level(64.0, 64.0);
+
+    for layer in &mut level.layers {
+        match layer {
+            Layer::Tile(layer) => {
+                assert_eq!(layer.coverage(), 0.0);
+
+                for x in 0..layer.grid_cells_x {
+                    layer.set(x, 0, Some(1));
+                }
+
+                assert_eq!(layer.coverage(), 0.25);
+            }
+
+            Layer::Grid(layer) => {
+                assert_eq!(layer.coverage("0"), 0.0);
+                assert_eq!(layer.coverage("1"), 1.0);
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[test]
+pub fn level_entities_and_decals() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let names: Vec<&str> = level.entities().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["Dude", "Grom", "nsad", "anchor"]);
+
+    let decal_count: usize = level
+        .layers
+        .iter()
+        .map(|l| match l {
+            Layer::Decal(l) => l.decals.len(),
+            _ => 0,
+        })
+        .sum();
+
+    assert!(decal_count > 0);
+    assert_eq!(level.decals().count(), decal_count);
+}
+
+#[test]
+pub fn level_tiles() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let mut expected = Vec::new();
+
+    for layer in &level.layers {
+        if let Layer::Tile(layer) = layer {
+            for tile in layer.unpack().filter(|t| t.id.is_some()) {
+                expected.push((layer.name.as_str(), tile));
+            }
+        }
+    }
+
+    let actual: Vec<_> = level
+        .tiles()
+        .map(|(layer, tile)| (layer.name.as_str(), tile))
+        .collect();
+
+    assert!(!actual.is_empty());
+    assert_eq!(actual, expected);
+}
+
+#[test]
+pub fn entities_with_tag() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for template in &mut project.entities {
+        if template.name == "Grom" || template.name == "nsad" {
+            template.tags.push("enemy".into());
+        }
+    }
+
+    let layer = level
+        .layers
+        .iter()
+        .find_map(|l| match l {
+            Layer::Entity(l) => Some(l),
+            _ => None,
+        })
+        .unwrap();
+
+    let enemies: Vec<&str> = layer
+        .entities_with_tag(&project, "enemy")
+        .map(|e| e.name.as_str())
+        .collect();
+
+    assert_eq!(enemies, ["Grom", "nsad"]);
+    assert_eq!(layer.entities_with_tag(&project, "missing").count(), 0);
+}
+
+#[test]
+pub fn entity_aabb() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let find = |name: &str| level.entities().find(|e| e.name == name).unwrap();
+    let template = |name: &str| project.entity_template_by_name(name).unwrap();
+
+    // Dude has its own size and origin.
+    let mut dude = find("Dude").clone();
+    dude.width = Some(32.0);
+    dude.origin_x = Some(4.0);
+
+    assert_eq!(
+        dude.aabb(template("Dude")),
+        (Vec2 { x: 108.0, y: 96.0 }, Vec2 { x: 140.0, y: 112.0 })
+    );
+
+    // Grom falls back to the template's size and origin (8, 0).
+    assert_eq!(
+        find("Grom").aabb(template("Grom")),
+        (Vec2 { x: 136.0, y: 80.0 }, Vec2 { x: 152.0, y: 96.0 })
+    );
+}
+
+#[test]
+pub fn convert_angles() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let half_turn = std::f32::consts::PI;
+
+    assert!(project.angles_radians);
+    assert_eq!(project.to_radians(half_turn), half_turn);
+    assert!((project.to_degrees(half_turn) - 180.0).abs() < 1e-4);
+
+    project.angles_radians = false;
+    assert!((project.to_radians(180.0) - half_turn).abs() < 1e-6);
+    assert_eq!(project.to_degrees(180.0), 180.0);
+}
+
+#[test]
+pub fn variant_accessors() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    assert_eq!(level.layers[0].as_tile().unwrap().name, "tile_1D");
+    assert!(level.layers[0].as_grid().is_none());
+    assert!(level.layers[2].as_tile_coords().is_some());
+    assert!(level.layers[4].as_grid().is_some());
+
+    level.layers[0].as_tile_mut().unwrap().set(0, 0, Some(3));
+    assert!(level.layers[0].as_entity_mut().is_none());
+
+    let tiles = level.layers.remove(0).into_tile().unwrap();
+    assert_eq!(tiles.get(0, 0).unwrap().id, Some(3));
+
+    let decals = level.layers.pop().unwrap();
+    assert!(decals.clone().into_entity().is_none());
+    assert_eq!(decals.into_decal().unwrap().decals.len(), 1);
+
+    assert_eq!(project.layers[0].as_tile().unwrap().name, "tile_1D");
+    assert!(project.layers[0].as_decal().is_none());
+    assert!(project.layers.iter().any(|l| l.as_entity().is_some()));
+
+    let poop = &project.level_values[0];
+    assert_eq!(poop.as_color().unwrap().defaults, "#000000ff");
+    assert!(poop.as_integer().is_none());
+    assert!(poop.clone().into_color().is_some());
+}
+
+#[test]
+pub fn tile_neighbors() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let layer = level.layers[0].as_tile().unwrap();
+
+    assert_eq!(
+        layer.neighbors(0, 0),
+        [None, None, Some(1), None, Some(1), None, None, None]
+    );
+
+    assert_eq!(
+        layer.neighbors(1, 1),
+        [Some(1), None, None, None, None, None, Some(1), Some(1)]
+    );
+
+    assert_eq!(layer.neighbors(-5, -5), [None; 8]);
+}
+
+#[test]
+pub fn entity_nodes() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let mut dude = level.entities().find(|e| e.name == "Dude").unwrap().clone();
+
+    dude.nodes = None;
+    assert_eq!(dude.nodes_iter().len(), 0);
+    assert_eq!(dude.node_segments().count(), 0);
+
+    let a = Vec2 { x: 0.0, y: 0.0 };
+    let b = Vec2 { x: 16.0, y: 0.0 };
+    let c = Vec2 { x: 16.0, y: 32.0 };
+
+    dude.nodes = Some(vec![a, b, c]);
+    assert_eq!(dude.nodes_iter().collect::<Vec<_>>(), [a, b, c]);
+    assert_eq!(dude.nodes_iter().next_back(), Some(c));
+    assert_eq!(dude.node_segments().collect::<Vec<_>>(), [(a, b), (b, c)]);
+}
+
+#[test]
+pub fn flatten_level() {
+    use ogmo3::level::{DecalTransform, FlatItem};
+
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let items = level.flatten(&project).unwrap();
+
+    assert_eq!(
+        items[0],
+        FlatItem::Tile {
+            tileset: 0,
+            id: 1,
+            position: Vec2 { x: 0.0, y: 0.0 },
+        }
+    );
+
+    assert!(items.iter().all(|item| match item {
+        FlatItem::Grid { value, .. } => *value != "0",
+        _ => true,
+    }));
+
+    let entities = items
+        .iter()
+        .filter(|item| matches!(item, FlatItem::Entity { .. }))
+        .count();
+
+    assert_eq!(entities, 4);
+
+    assert_eq!(
+        items.last(),
+        Some(&FlatItem::Decal {
+            texture: "img/tiles.png".into(),
+            transform: DecalTransform {
+                position: Vec2 { x: 16.0, y: 232.0 },
+                rotation: 0.0,
+                scale: Vec2 { x: 1.0, y: 1.0 },
+            },
+        })
+    );
+
+    let mut level = level;
+
+    if let Layer::Tile(layer) = &mut level.layers[0] {
+        layer.tileset = "Missing Tileset".into();
+    }
+
+    assert!(level.flatten(&project).is_err());
+}
+
+#[test]
+pub fn filled_counts() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) => {
+                let expected = layer.unpack().filter(|tile| tile.id.is_some()).count();
+
+                assert!(expected > 0);
+                assert_eq!(layer.filled_count(), expected);
+            }
+
+            Layer::Grid(layer) => {
+                let expected = layer.unpack().filter(|cell| cell.value != "0").count();
+
+                assert!(expected > 0);
+                assert_eq!(layer.filled_count(), expected);
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[test]
+pub fn layer_views() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) => {
+                let view = layer.view();
+
+                assert_eq!(view.dimensions(), Vec2 { x: 20, y: 15 });
+                assert!(view.contains(19, 14));
+                assert!(!view.contains(20, 0));
+                assert_eq!(view.get(20, 0), None);
+
+                for tile in view.iter() {
+                    assert_eq!(
+                        view.get(tile.grid_position.x, tile.grid_position.y),
+                        tile.id
+                    );
+                }
+            }
+
+            Layer::Grid(layer) => {
+                let view = layer.view();
+
+                assert_eq!(
+                    view.dimensions(),
+                    Vec2 {
+                        x: layer.grid_cells_x,
+                        y: layer.grid_cells_y
+                    }
+                );
+                assert_eq!(view.get(-1, 0), None);
+
+                for cell in view.iter() {
+                    assert_eq!(
+                        view.get(cell.grid_position.x, cell.grid_position.y),
+                        Some(cell.value)
+                    );
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+#[test]
+pub fn undescribed_grid_values() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let mut template = match project.layer_template_by_name("grid_1D") {
+        Some(LayerTemplate::Grid(t)) => t.clone(),
+        _ => panic!("expected a grid layer template"),
+    };
+
+    let layer = match level.layers.get(4) {
+        Some(Layer::Grid(l)) => l,
+        _ => panic!("expected a grid layer"),
+    };
+
+    assert!(layer.undescribed_values(&template).is_empty());
+
+    template.legend.retain(|k, _| k != "3" && k != "a");
+
+    let undescribed = layer.undescribed_values(&template);
+
+    assert_eq!(undescribed.len(), 1);
+    assert!(undescribed.contains("3"));
+}
+
+#[test]
+pub fn content_bounds() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    assert_eq!(
+        level.content_bounds(),
+        (Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 320.0, y: 240.0 })
+    );
+
+    level.layers.retain(|l| matches!(l, Layer::Decal(_)));
+
+    if let Some(Layer::Decal(layer)) = level.layers.get_mut(0) {
+        layer.offset_x = 8.0;
+    }
+
+    assert_eq!(
+        level.content_bounds(),
+        (Vec2 { x: 24.0, y: 232.0 }, Vec2 { x: 24.0, y: 232.0 })
+    );
+
+    level.layers.clear();
+
+    assert_eq!(
+        level.content_bounds(),
+        (
+            Vec2 { x: 0.0, y: 0.0 },
+            Vec2 {
+                x: level.width,
+                y: level.height
+            }
+        )
+    );
+}
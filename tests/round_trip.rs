@@ -1,6 +1,8 @@
 use std::fs;
 
-use ogmo3::{Level, Project};
+use ogmo3::level::{TileCoordsLayer, TileCoordsLayerStorage};
+use ogmo3::project::ArrayMode;
+use ogmo3::{Layer, Level, PrettyOptions, Project};
 use pretty_assertions::assert_eq;
 use serde_json::Value;
 
@@ -9,10 +11,7 @@ pub fn round_trip_project() {
     let input = fs::read_to_string("./examples/sample_project/test.ogmo").unwrap();
     let project = Project::from_json(&input).unwrap();
 
-    // Serde always includes decimal places on floats, even if they're whole numbers,
-    // so we have to hack around that to get output that matches what
-    // Ogmo gives us.
-    let output = project.to_json().unwrap().replace(".0", "");
+    let output = project.to_json().unwrap();
 
     let input_json: Value = serde_json::from_str(&input).unwrap();
     let output_json: Value = serde_json::from_str(&output).unwrap();
@@ -25,13 +24,375 @@ pub fn round_trip_level() {
     let input = fs::read_to_string("./examples/sample_project/levels/uno.json").unwrap();
     let level = Level::from_json(&input).unwrap();
 
-    // Serde always includes decimal places on floats, even if they're whole numbers,
-    // so we have to hack around that to get output that matches what
-    // Ogmo gives us.
-    let output = level.to_json().unwrap().replace(".0", "");
+    let output = level.to_json().unwrap();
 
     let input_json: Value = serde_json::from_str(&input).unwrap();
     let output_json: Value = serde_json::from_str(&output).unwrap();
 
     assert_eq!(input_json, output_json);
 }
+
+#[test]
+pub fn round_trip_minified_level() {
+    let input = fs::read_to_string("./examples/sample_project/levels/uno.json").unwrap();
+    let level = Level::from_json(&input).unwrap();
+
+    let minified = level.to_json_minified().unwrap();
+    assert!(!minified.contains("offsetX"));
+    assert!(!minified.contains("offsetY"));
+
+    let output = Level::from_json(&minified).unwrap().to_json().unwrap();
+
+    let input_json: Value = serde_json::from_str(&input).unwrap();
+    let output_json: Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(input_json, output_json);
+}
+
+#[test]
+pub fn whole_floats_written_as_integers() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    if let Some(Layer::Entity(layer)) = level.layers.iter_mut().find(|l| l.name() == "entity_layer")
+    {
+        layer.entities[0].x = 1.5;
+        layer.entities[1].x = 10.3;
+    }
+
+    level.offset_x = 0.1;
+
+    let output = level.to_json().unwrap();
+
+    assert!(output.contains(r#""width":320,"height":240,"offsetX":0.1,"offsetY":0"#));
+    assert!(output.contains(r#""x":1.5"#));
+    assert!(output.contains(r#""x":10.3"#));
+
+    let pretty = level.to_json_pretty().unwrap();
+
+    assert!(pretty.contains(r#""width": 320,"#));
+    assert!(pretty.contains(r#""offsetX": 0.1,"#));
+
+    let minified = level.to_json_minified().unwrap();
+
+    assert!(minified.contains(r#""offsetX":0.1"#));
+    assert!(minified.contains(r#""x":10.3"#));
+
+    let sorted = level
+        .to_json_pretty_with(PrettyOptions {
+            sort_keys: true,
+            ..PrettyOptions::default()
+        })
+        .unwrap();
+
+    assert!(sorted.contains(r#""offsetX": 0.1,"#));
+    assert!(sorted.contains(r#""x": 10.3,"#));
+    assert!(sorted.contains(r#""width": 320"#));
+}
+
+#[test]
+#[cfg(not(feature = "preserve-order"))]
+pub fn values_written_in_sorted_order() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for key in &["zeta", "alpha", "mu", "beta", "omega", "gamma"] {
+        level
+            .values
+            .insert(key.to_string(), ogmo3::Value::Boolean(true));
+    }
+
+    let output = level.to_json().unwrap();
+    let positions: Vec<usize> = ["alpha", "beta", "gamma", "mu", "omega", "poop", "zeta"]
+        .iter()
+        .map(|key| output.find(&format!("\"{}\"", key)).unwrap())
+        .collect();
+
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+#[cfg(feature = "preserve-order")]
+pub fn values_written_in_insertion_order() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for key in &["zeta", "alpha", "mu", "beta", "omega", "gamma"] {
+        level
+            .values
+            .insert(key.to_string(), ogmo3::Value::Boolean(true));
+    }
+
+    let output = level.to_json().unwrap();
+    let positions: Vec<usize> = ["poop", "zeta", "alpha", "mu", "beta", "omega", "gamma"]
+        .iter()
+        .map(|key| output.find(&format!("\"{}\"", key)).unwrap())
+        .collect();
+
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+    let reparsed = Level::from_json(&output).unwrap();
+    assert_eq!(reparsed.to_json().unwrap(), output);
+}
+
+#[test]
+pub fn serialization_is_deterministic() {
+    let mut outputs = Vec::new();
+
+    for _ in 0..2 {
+        let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+        for key in &["zeta", "alpha", "mu", "beta", "omega", "gamma"] {
+            level.extra.insert(key.to_string(), Value::from(1));
+        }
+
+        for layer in &mut level.layers {
+            if let Layer::Entity(layer) = layer {
+                for (i, entity) in layer.entities.iter_mut().enumerate() {
+                    for key in &["x", "y", "z", "w", "v"] {
+                        entity.set_value(format!("{}{}", key, i), ogmo3::Value::Integer(1));
+                        entity
+                            .extra
+                            .insert(format!("extra_{}{}", key, i), Value::from(1));
+                    }
+                }
+            }
+        }
+
+        outputs.push(level.to_json_pretty().unwrap());
+    }
+
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+pub fn round_trip_level_with_project_formatting() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let input = fs::read_to_string("./examples/sample_project/levels/uno.json").unwrap();
+    let level = Level::from_json(&input).unwrap();
+
+    assert_eq!(level.to_json_with_project(&project).unwrap(), input);
+
+    project.compact_export = true;
+
+    assert_eq!(
+        level.to_json_with_project(&project).unwrap(),
+        level.to_json().unwrap()
+    );
+}
+
+#[test]
+pub fn round_trip_equality() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let reparsed_project = Project::from_json(&project.to_json().unwrap()).unwrap();
+
+    assert_eq!(
+        Project {
+            base_directory: None,
+            ..project
+        },
+        reparsed_project
+    );
+
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let mut reparsed_level = Level::from_json(&level.to_json().unwrap()).unwrap();
+
+    assert_eq!(level, reparsed_level);
+
+    if let Some(Layer::Entity(layer)) = reparsed_level.layers.get_mut(6) {
+        layer.entities[0].x += 1.0;
+    }
+
+    assert_ne!(level, reparsed_level);
+}
+
+#[test]
+pub fn round_trip_empty_values() {
+    let input = concat!(
+        r#"{"ogmoVersion":"3.4.0","width":320,"height":240,"offsetX":0,"offsetY":0,"layers":["#,
+        r#"{"name":"entities","_eid":"1","offsetX":0,"offsetY":0,"gridCellWidth":8,"gridCellHeight":8,"#,
+        r#""gridCellsX":40,"gridCellsY":30,"entities":["#,
+        r#"{"name":"with_values","id":0,"_eid":"2","x":0,"y":0,"values":{}},"#,
+        r#"{"name":"without_values","id":1,"_eid":"3","x":8,"y":8}"#,
+        r#"]}]}"#
+    );
+
+    let level = Level::from_json(input).unwrap();
+
+    assert!(level.values.is_empty());
+    assert_eq!(level.to_json().unwrap(), input);
+}
+
+#[test]
+pub fn round_trip_long_empty_tile_coords() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let original = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let input = fs::read_to_string("./examples/sample_project/levels/uno.json")
+        .unwrap()
+        .replace("[-1]", "[-1, -1]");
+
+    let mut level = Level::from_json(&input).unwrap();
+
+    assert_eq!(level.to_json_with_project(&project).unwrap(), input);
+    assert!(level.validate(&project).is_ok());
+
+    for (layer, original_layer) in level.layers.iter().zip(&original.layers) {
+        if let (Layer::TileCoords(layer), Layer::TileCoords(original_layer)) =
+            (layer, original_layer)
+        {
+            assert_eq!(layer.data.empty_coords(), vec![-1, -1]);
+            assert!(layer.unpack().eq(original_layer.unpack()));
+        }
+    }
+
+    if let Some(Layer::TileCoords(layer)) = level.layers.get_mut(3) {
+        layer.grid_cells_x += 1;
+    }
+
+    level.set_storage_mode(ArrayMode::One);
+
+    match level.layers.get(3) {
+        Some(Layer::TileCoords(TileCoordsLayer {
+            grid_cells_x,
+            data: TileCoordsLayerStorage::DataCoords(data),
+            ..
+        })) => assert_eq!(data[*grid_cells_x as usize - 1], vec![-1, -1]),
+        other => panic!("expected 1D tile co-ords layer, got {:?}", other),
+    }
+}
+
+#[test]
+pub fn round_trip_unknown_layers() {
+    // The unknown layer's fields are sorted, as their original order is only preserved
+    // with the `preserve-order` feature.
+    let input = concat!(
+        r#"{"ogmoVersion":"3.4.0","width":320,"height":240,"offsetX":0,"offsetY":0,"layers":["#,
+        r#"{"_eid":"1","gridCellWidth":8,"lights":[{"radius":16,"x":4,"y":4}],"name":"lights"},"#,
+        r#"{"name":"entities","_eid":"2","offsetX":0,"offsetY":0,"gridCellWidth":8,"gridCellHeight":8,"#,
+        r#""gridCellsX":40,"gridCellsY":30,"entities":[]}"#,
+        r#"]}"#
+    );
+
+    let level = Level::from_json(input).unwrap();
+
+    assert!(matches!(level.layers[0], Layer::Unknown(_)));
+    assert_eq!(level.layers[0].name(), "lights");
+    assert_eq!(level.layers[0].export_id(), "1");
+    assert!(matches!(level.layers[1], Layer::Entity(_)));
+
+    assert_eq!(level.to_json().unwrap(), input);
+}
+
+#[test]
+#[cfg(feature = "preserve-order")]
+pub fn round_trip_unknown_layers_in_order() {
+    let input = concat!(
+        r#"{"ogmoVersion":"3.4.0","width":320,"height":240,"offsetX":0,"offsetY":0,"layers":["#,
+        r#"{"name":"lights","_eid":"1","lights":[{"x":4,"y":4,"radius":16}],"gridCellWidth":8}"#,
+        r#"]}"#
+    );
+
+    let level = Level::from_json(input).unwrap();
+
+    assert!(matches!(level.layers[0], Layer::Unknown(_)));
+    assert_eq!(level.to_json().unwrap(), input);
+}
+
+#[test]
+pub fn malformed_known_layers_are_errors() {
+    let input = concat!(
+        r#"{"ogmoVersion":"3.4.0","width":320,"height":240,"offsetX":0,"offsetY":0,"layers":["#,
+        r#"{"name":"tiles","_eid":"1","gridCellWidth":8,"gridCellHeight":8,"#,
+        r#""gridCellsX":40,"gridCellsY":30,"tileset":"tiles","data":"oops"}"#,
+        r#"]}"#
+    );
+
+    assert!(Level::from_json(input).is_err());
+}
+
+#[test]
+pub fn round_trip_extra_fields() {
+    let input = fs::read_to_string("./examples/sample_project/test.ogmo").unwrap();
+    let mut json: Value = serde_json::from_str(&input).unwrap();
+
+    json["futureSetting"] = Value::from(true);
+    json["layers"][0]["futureLayerSetting"] = Value::from(1);
+    json["entities"][0]["futureEntitySetting"] = Value::from("a");
+    json["entities"][0]["values"][0]["futureValueSetting"] = Value::from(2.5);
+
+    let project = Project::from_json(&json.to_string()).unwrap();
+
+    assert_eq!(project.extra["futureSetting"], Value::from(true));
+
+    let output = project.to_json().unwrap();
+
+    // The layer templates' `definition` tag shouldn't be duplicated.
+    assert_eq!(
+        output.matches(r#""definition":"tile""#).count(),
+        input.matches(r#""definition": "tile""#).count()
+    );
+
+    assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), json);
+
+    let input = fs::read_to_string("./examples/sample_project/levels/uno.json").unwrap();
+    let mut json: Value = serde_json::from_str(&input).unwrap();
+
+    json["futureSetting"] = Value::from(false);
+    json["layers"][6]["entities"][0]["futureEntitySetting"] = Value::from(3);
+
+    let level = Level::from_json(&json.to_string()).unwrap();
+
+    assert_eq!(level.extra["futureSetting"], Value::from(false));
+    assert_eq!(
+        serde_json::from_str::<Value>(&level.to_json().unwrap()).unwrap(),
+        json
+    );
+}
+
+#[test]
+pub fn pretty_print_with_options() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    assert_eq!(
+        level.to_json_pretty_with(PrettyOptions::default()).unwrap(),
+        level.to_json_pretty().unwrap()
+    );
+
+    assert_eq!(
+        project
+            .to_json_pretty_with(PrettyOptions::default())
+            .unwrap(),
+        project.to_json_pretty().unwrap()
+    );
+
+    let tabs = PrettyOptions {
+        indent: "\t".into(),
+        ..PrettyOptions::default()
+    };
+
+    let json = level.to_json_pretty_with(tabs.clone()).unwrap();
+
+    assert!(json.starts_with("{\n\t\"ogmoVersion\": \"3.2.2\",\n\t\"width\""));
+    assert!(json.contains("\n\t\t{\n\t\t\t\"name\""));
+    assert_eq!(Level::from_json(&json).unwrap(), level);
+
+    let sorted = PrettyOptions {
+        sort_keys: true,
+        ..tabs
+    };
+
+    let json = level.to_json_pretty_with(sorted.clone()).unwrap();
+
+    assert!(json.starts_with("{\n\t\"height\": 240,\n\t\"layers\""));
+    assert_eq!(Level::from_json(&json).unwrap(), level);
+
+    let json = project.to_json_pretty_with(sorted).unwrap();
+
+    assert!(json.starts_with("{\n\t\"anglesRadians\""));
+    assert_eq!(
+        Project::from_json(&json).unwrap(),
+        Project {
+            base_directory: None,
+            ..project
+        }
+    );
+}
@@ -0,0 +1,606 @@
+//! Property-based round trip tests, which generate arbitrary levels and projects
+//! and check that they are unchanged after being serialized and deserialized again.
+//!
+//! proptest's `Arbitrary` trait can't be implemented for this crate's types from
+//! an integration test, so the generators are written as strategy functions instead.
+
+use std::fmt::Debug;
+
+use ogmo3::level::{
+    Decal, DecalLayer, Entity, EntityLayer, GridLayer, GridLayerStorage, TileCoordsLayer,
+    TileCoordsLayerStorage, TileLayer, TileLayerStorage,
+};
+use ogmo3::project::{
+    ArrayMode, BooleanValueTemplate, ColorValueTemplate, DecalLayerTemplate, EntityLayerTemplate,
+    EntityTemplate, EnumValueTemplate, ExportMode, FloatValueTemplate, GridLayerTemplate,
+    IntegerValueTemplate, LayerTemplate, Shape, StringValueTemplate, TextValueTemplate,
+    TileLayerTemplate, Tileset, ValueTemplate,
+};
+use ogmo3::{Layer, Level, Project, Value, ValueMap, Vec2};
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+/// Generates any finite float - JSON has no way of representing NaN or infinity.
+fn float() -> impl Strategy<Value = f32> + Clone {
+    any::<f32>().prop_filter("JSON can't represent NaN or infinity", |v| v.is_finite())
+}
+
+fn small_int() -> impl Strategy<Value = i32> + Clone {
+    0..64
+}
+
+fn string() -> impl Strategy<Value = String> + Clone {
+    any::<String>()
+}
+
+fn int_vec2() -> impl Strategy<Value = Vec2<i32>> + Clone {
+    (small_int(), small_int()).prop_map(|(x, y)| Vec2 { x, y })
+}
+
+fn float_vec2() -> impl Strategy<Value = Vec2<f32>> + Clone {
+    (float(), float()).prop_map(|(x, y)| Vec2 { x, y })
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<bool>().prop_map(Value::Boolean),
+        string().prop_map(Value::String),
+        any::<i64>().prop_map(Value::Integer),
+        // Whole numbers are written without a decimal point, so they are read back in
+        // as integers.
+        float()
+            .prop_filter("whole numbers are read as integers", |v| v.fract() != 0.0)
+            .prop_map(Value::Number),
+    ]
+}
+
+fn values() -> impl Strategy<Value = ValueMap<String, Value>> {
+    vec((string(), value()), 0..4).prop_map(|values| values.into_iter().collect())
+}
+
+/// Generates fields that the crate doesn't model. The keys are prefixed so that they
+/// can't clash with any of the known fields.
+fn extra() -> impl Strategy<Value = ValueMap<String, serde_json::Value>> {
+    vec(("extra_[a-z]{1,6}", string()), 0..3).prop_map(|fields| {
+        fields
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect()
+    })
+}
+
+fn entity() -> impl Strategy<Value = Entity> {
+    (
+        (string(), 0..1000, string(), float(), float()),
+        (
+            option::of(float()),
+            option::of(float()),
+            option::of(float()),
+            option::of(float()),
+            option::of(float()),
+        ),
+        (
+            option::of(any::<bool>()),
+            option::of(any::<bool>()),
+            option::of(vec(float_vec2(), 0..4)),
+            option::of(values()),
+            extra(),
+        ),
+    )
+        .prop_map(
+            |(
+                (name, id, export_id, x, y),
+                (width, height, origin_x, origin_y, rotation),
+                (flipped_x, flipped_y, nodes, values, extra),
+            )| Entity {
+                name,
+                id,
+                export_id,
+                x,
+                y,
+                width,
+                height,
+                origin_x,
+                origin_y,
+                rotation,
+                flipped_x,
+                flipped_y,
+                nodes,
+                values,
+                extra,
+            },
+        )
+}
+
+fn decal() -> impl Strategy<Value = Decal> {
+    (
+        float(),
+        float(),
+        option::of(float()),
+        option::of(float()),
+        option::of(float()),
+        string(),
+        values(),
+    )
+        .prop_map(
+            |(x, y, scale_x, scale_y, rotation, texture, values)| Decal {
+                x,
+                y,
+                scale_x,
+                scale_y,
+                rotation,
+                texture,
+                values,
+            },
+        )
+}
+
+/// Generates the size of a layer, and a grid of cells to fill it with.
+fn cells<T: Debug>(
+    cell: impl Strategy<Value = T> + Clone,
+) -> impl Strategy<Value = (Vec2<i32>, Vec<Vec<T>>)> {
+    (1..=8, 0..=8).prop_flat_map(move |(x, y)| {
+        vec(vec(cell.clone(), x as usize), y as usize).prop_map(move |rows| (Vec2 { x, y }, rows))
+    })
+}
+
+/// The fields that are shared by every type of layer.
+#[derive(Clone, Debug)]
+struct LayerInfo {
+    name: String,
+    export_id: String,
+    offset: Vec2<f32>,
+    cell_size: Vec2<i32>,
+}
+
+fn layer_info() -> impl Strategy<Value = LayerInfo> {
+    (string(), string(), float_vec2(), (1..32, 1..32)).prop_map(
+        |(name, export_id, offset, (x, y))| LayerInfo {
+            name,
+            export_id,
+            offset,
+            cell_size: Vec2 { x, y },
+        },
+    )
+}
+
+fn layer() -> impl Strategy<Value = Layer> {
+    prop_oneof![
+        (layer_info(), cells(-1..64), string(), any::<bool>()).prop_map(
+            |(info, (size, rows), tileset, flat)| {
+                Layer::Tile(TileLayer {
+                    name: info.name,
+                    export_id: info.export_id,
+                    offset_x: info.offset.x,
+                    offset_y: info.offset.y,
+                    grid_cell_width: info.cell_size.x,
+                    grid_cell_height: info.cell_size.y,
+                    grid_cells_x: size.x,
+                    grid_cells_y: size.y,
+                    tileset,
+                    data: if flat {
+                        TileLayerStorage::Data(rows.concat())
+                    } else {
+                        TileLayerStorage::Data2D(rows)
+                    },
+                })
+            }
+        ),
+        (
+            layer_info(),
+            cells(prop_oneof![
+                Just(vec![-1]),
+                (0..16, 0..16).prop_map(|(x, y)| vec![x, y]),
+            ]),
+            string(),
+            any::<bool>(),
+        )
+            .prop_map(|(info, (size, rows), tileset, flat)| {
+                Layer::TileCoords(TileCoordsLayer {
+                    name: info.name,
+                    export_id: info.export_id,
+                    offset_x: info.offset.x,
+                    offset_y: info.offset.y,
+                    grid_cell_width: info.cell_size.x,
+                    grid_cell_height: info.cell_size.y,
+                    grid_cells_x: size.x,
+                    grid_cells_y: size.y,
+                    tileset,
+                    data: if flat {
+                        TileCoordsLayerStorage::DataCoords(rows.concat())
+                    } else {
+                        TileCoordsLayerStorage::DataCoords2D(rows)
+                    },
+                })
+            }),
+        (layer_info(), cells(string()), any::<bool>()).prop_map(|(info, (size, rows), flat)| {
+            Layer::Grid(GridLayer {
+                name: info.name,
+                export_id: info.export_id,
+                offset_x: info.offset.x,
+                offset_y: info.offset.y,
+                grid_cell_width: info.cell_size.x,
+                grid_cell_height: info.cell_size.y,
+                grid_cells_x: size.x,
+                grid_cells_y: size.y,
+                data: if flat {
+                    GridLayerStorage::Grid(rows.concat())
+                } else {
+                    GridLayerStorage::Grid2D(rows)
+                },
+            })
+        }),
+        (layer_info(), int_vec2(), vec(entity(), 0..4)).prop_map(|(info, cells, entities)| {
+            Layer::Entity(EntityLayer {
+                name: info.name,
+                export_id: info.export_id,
+                offset_x: info.offset.x,
+                offset_y: info.offset.y,
+                grid_cell_width: info.cell_size.x,
+                grid_cell_height: info.cell_size.y,
+                grid_cells_x: cells.x,
+                grid_cells_y: cells.y,
+                entities,
+            })
+        }),
+        (layer_info(), int_vec2(), vec(decal(), 0..4), string()).prop_map(
+            |(info, cells, decals, folder)| {
+                Layer::Decal(DecalLayer {
+                    name: info.name,
+                    export_id: info.export_id,
+                    offset_x: info.offset.x,
+                    offset_y: info.offset.y,
+                    grid_cell_width: info.cell_size.x,
+                    grid_cell_height: info.cell_size.y,
+                    grid_cells_x: cells.x,
+                    grid_cells_y: cells.y,
+                    decals,
+                    folder,
+                })
+            }
+        ),
+    ]
+}
+
+fn level() -> impl Strategy<Value = Level> {
+    (
+        float_vec2(),
+        float_vec2(),
+        values(),
+        vec(layer(), 0..6),
+        extra(),
+    )
+        .prop_map(|(size, offset, values, layers, extra)| Level {
+            ogmo_version: "3.2.2".into(),
+            width: size.x,
+            height: size.y,
+            offset_x: offset.x,
+            offset_y: offset.y,
+            values,
+            layers,
+            extra,
+        })
+}
+
+fn value_template() -> impl Strategy<Value = ValueTemplate> {
+    prop_oneof![
+        (string(), any::<bool>()).prop_map(|(name, defaults)| {
+            ValueTemplate::Boolean(BooleanValueTemplate {
+                name,
+                defaults,
+                extra: Default::default(),
+            })
+        }),
+        (string(), any::<bool>()).prop_map(|(name, include_alpha)| {
+            ValueTemplate::Color(ColorValueTemplate {
+                name,
+                defaults: "#ff00ffff".into(),
+                include_alpha,
+                extra: Default::default(),
+            })
+        }),
+        (string(), 0..3, vec(string(), 3)).prop_map(|(name, defaults, choices)| {
+            ValueTemplate::Enum(EnumValueTemplate {
+                name,
+                defaults,
+                choices,
+                extra: Default::default(),
+            })
+        }),
+        (
+            string(),
+            any::<i32>(),
+            any::<bool>(),
+            any::<i32>(),
+            any::<i32>()
+        )
+            .prop_map(|(name, defaults, bounded, min, max)| {
+                ValueTemplate::Integer(IntegerValueTemplate {
+                    name,
+                    defaults,
+                    bounded,
+                    min,
+                    max,
+                    extra: Default::default(),
+                })
+            }),
+        (string(), float(), any::<bool>(), float(), float()).prop_map(
+            |(name, defaults, bounded, min, max)| {
+                ValueTemplate::Float(FloatValueTemplate {
+                    name,
+                    defaults,
+                    bounded,
+                    min,
+                    max,
+                    extra: Default::default(),
+                })
+            }
+        ),
+        (string(), string(), any::<i32>(), any::<bool>()).prop_map(
+            |(name, defaults, max_length, trim_whitespace)| {
+                ValueTemplate::String(StringValueTemplate {
+                    name,
+                    defaults,
+                    max_length,
+                    trim_whitespace,
+                    extra: Default::default(),
+                })
+            }
+        ),
+        (string(), string()).prop_map(|(name, defaults)| {
+            ValueTemplate::Text(TextValueTemplate {
+                name,
+                defaults,
+                extra: Default::default(),
+            })
+        }),
+    ]
+}
+
+fn array_mode() -> impl Strategy<Value = ArrayMode> {
+    prop_oneof![Just(ArrayMode::One), Just(ArrayMode::Two)]
+}
+
+fn layer_template() -> impl Strategy<Value = LayerTemplate> {
+    let common = (string(), int_vec2(), string());
+
+    prop_oneof![
+        (
+            common.clone(),
+            prop_oneof![Just(ExportMode::Ids), Just(ExportMode::Coords)],
+            array_mode(),
+            string(),
+        )
+            .prop_map(
+                |((name, grid_size, export_id), export_mode, array_mode, default_tileset)| {
+                    LayerTemplate::Tile(TileLayerTemplate {
+                        name,
+                        grid_size,
+                        export_id,
+                        export_mode,
+                        array_mode,
+                        default_tileset,
+                        extra: Default::default(),
+                    })
+                }
+            ),
+        (
+            common.clone(),
+            array_mode(),
+            vec(("[0-9]", Just(String::from("#000000ff"))), 0..4),
+        )
+            .prop_map(|((name, grid_size, export_id), array_mode, legend)| {
+                LayerTemplate::Grid(GridLayerTemplate {
+                    name,
+                    grid_size,
+                    export_id,
+                    array_mode,
+                    legend: legend.into_iter().collect(),
+                    extra: Default::default(),
+                })
+            }),
+        (common.clone(), vec(string(), 0..3), vec(string(), 0..3)).prop_map(
+            |((name, grid_size, export_id), required_tags, excluded_tags)| {
+                LayerTemplate::Entity(EntityLayerTemplate {
+                    name,
+                    grid_size,
+                    export_id,
+                    required_tags,
+                    excluded_tags,
+                    extra: Default::default(),
+                })
+            }
+        ),
+        (
+            common,
+            string(),
+            (any::<bool>(), any::<bool>(), any::<bool>()),
+            vec(value_template(), 0..3),
+        )
+            .prop_map(
+                |(
+                    (name, grid_size, export_id),
+                    folder,
+                    (include_image_sequence, scaleable, rotatable),
+                    values,
+                )| {
+                    LayerTemplate::Decal(DecalLayerTemplate {
+                        name,
+                        grid_size,
+                        export_id,
+                        folder,
+                        include_image_sequence,
+                        scaleable,
+                        rotatable,
+                        values,
+                        extra: Default::default(),
+                    })
+                }
+            ),
+    ]
+}
+
+fn entity_template() -> impl Strategy<Value = EntityTemplate> {
+    (
+        (
+            string(),
+            string(),
+            -1..10,
+            float_vec2(),
+            float_vec2(),
+            any::<bool>(),
+        ),
+        (string(), vec(float_vec2(), 0..6)),
+        (any::<bool>(), any::<bool>(), float_vec2()),
+        (any::<bool>(), any::<bool>(), any::<bool>(), float()),
+        (any::<bool>(), any::<bool>(), any::<bool>()),
+        (any::<bool>(), 0..10, 0..2, any::<bool>()),
+        (
+            vec(string(), 0..3),
+            vec(value_template(), 0..3),
+            option::of(string()),
+            option::of(string()),
+        ),
+    )
+        .prop_map(
+            |(
+                (name, export_id, limit, size, origin, origin_anchored),
+                (shape_label, shape_points),
+                (tile_x, tile_y, tile_size),
+                (resizeable_x, resizeable_y, rotatable, rotation_degrees),
+                (can_flip_x, can_flip_y, can_set_color),
+                (has_nodes, node_limit, node_display, node_ghost),
+                (tags, values, texture, texture_image),
+            )| EntityTemplate {
+                name,
+                export_id,
+                limit,
+                size,
+                origin,
+                origin_anchored,
+                shape: Shape {
+                    label: shape_label,
+                    points: shape_points,
+                },
+                color: "#ff0000ff".into(),
+                tile_x,
+                tile_y,
+                tile_size,
+                resizeable_x,
+                resizeable_y,
+                rotatable,
+                rotation_degrees,
+                can_flip_x,
+                can_flip_y,
+                can_set_color,
+                has_nodes,
+                node_limit,
+                node_display,
+                node_ghost,
+                tags,
+                values,
+                texture,
+                texture_image,
+                extra: Default::default(),
+            },
+        )
+}
+
+fn tileset() -> impl Strategy<Value = Tileset> {
+    ((string(), string(), string()), (1..32, 1..32, 0..4, 0..4)).prop_map(
+        |(
+            (label, path, image),
+            (tile_width, tile_height, tile_separation_x, tile_separation_y),
+        )| Tileset {
+            label,
+            path,
+            image,
+            tile_width,
+            tile_height,
+            tile_separation_x,
+            tile_separation_y,
+        },
+    )
+}
+
+fn project() -> impl Strategy<Value = Project> {
+    (
+        (string(), vec(string(), 0..3), any::<bool>(), 0..10),
+        (int_vec2(), int_vec2(), int_vec2(), int_vec2()),
+        (
+            vec(value_template(), 0..4),
+            any::<bool>(),
+            vec(string(), 0..3),
+        ),
+        (
+            vec(layer_template(), 0..6),
+            vec(entity_template(), 0..4),
+            vec(tileset(), 0..3),
+            vec(level(), 0..2),
+        ),
+        extra(),
+    )
+        .prop_map(
+            |(
+                (name, level_paths, angles_radians, directory_depth),
+                (layer_grid_default_size, level_default_size, level_min_size, level_max_size),
+                (level_values, compact_export, entity_tags),
+                (layers, entities, tilesets, levels),
+                extra,
+            )| Project {
+                name,
+                ogmo_version: "3.2.2".into(),
+                level_paths,
+                background_color: "#282c34ff".into(),
+                grid_color: "#3c4049cc".into(),
+                angles_radians,
+                directory_depth,
+                layer_grid_default_size,
+                level_default_size,
+                level_min_size,
+                level_max_size,
+                level_values,
+                default_export_mode: ".json".into(),
+                compact_export,
+                entity_tags,
+                layers,
+                entities,
+                tilesets,
+                levels,
+                base_directory: None,
+                extra,
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn round_trip_arbitrary_levels(level in level()) {
+        let json = level.to_json().unwrap();
+        prop_assert_eq!(Level::from_json(&json).unwrap(), level);
+    }
+
+    /// Round tripping only checks that floats parse back to the same value - this
+    /// also checks that they are written in their shortest form, rather than being
+    /// widened to an f64 first.
+    #[test]
+    fn floats_written_in_shortest_form(level in level(), offset in float()) {
+        let mut level = level;
+        level.offset_x = offset;
+
+        let expected = if offset.fract() == 0.0 && f64::from(offset.abs()) <= 9_007_199_254_740_991.0 {
+            format!(r#""offsetX":{}"#, offset as i64)
+        } else {
+            format!(r#""offsetX":{}"#, serde_json::to_string(&offset).unwrap())
+        };
+
+        prop_assert!(level.to_json().unwrap().contains(&expected));
+    }
+
+    #[test]
+    fn round_trip_arbitrary_projects(project in project()) {
+        let json = project.to_json().unwrap();
+        prop_assert_eq!(Project::from_json(&json).unwrap(), project);
+    }
+}
@@ -0,0 +1,42 @@
+use ogmo3::project::Shape;
+use ogmo3::{Project, Vec2};
+
+fn point(x: f32, y: f32) -> Vec2<f32> {
+    Vec2 { x, y }
+}
+
+#[test]
+pub fn sample_shape() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let shape = &project.entities[0].shape;
+
+    assert_eq!(shape.area(), 4.0);
+
+    assert!(shape.contains_point(point(0.0, 0.0)));
+    assert!(shape.contains_point(point(0.9, 0.9)));
+    assert!(shape.contains_point(point(-1.0, 1.0)));
+    assert!(!shape.contains_point(point(1.5, 0.0)));
+}
+
+#[test]
+pub fn triangle_shape() {
+    let shape = Shape {
+        label: "Triangle".into(),
+        points: vec![point(0.0, 0.0), point(4.0, 0.0), point(0.0, 4.0)],
+    };
+
+    assert_eq!(shape.area(), 8.0);
+    assert!(shape.contains_point(point(1.0, 1.0)));
+    assert!(!shape.contains_point(point(3.0, 3.0)));
+}
+
+#[test]
+pub fn degenerate_shape() {
+    let shape = Shape {
+        label: "Line".into(),
+        points: vec![point(0.0, 0.0), point(4.0, 0.0)],
+    };
+
+    assert_eq!(shape.area(), 0.0);
+    assert!(!shape.contains_point(point(0.0, 0.0)));
+}
@@ -0,0 +1,63 @@
+use ogmo3::{Layer, Level, Project, Vec2};
+
+#[test]
+pub fn tensor_dimensions_match_layers() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let tensor = level.to_tensor(&project);
+
+    let layers: Vec<(i32, i32)> = level
+        .layers
+        .iter()
+        .filter_map(|layer| match layer {
+            Layer::Tile(l) => Some((l.grid_cells_x, l.grid_cells_y)),
+            Layer::Grid(l) => Some((l.grid_cells_x, l.grid_cells_y)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(tensor.len(), layers.len());
+
+    for (cells, (cells_x, cells_y)) in tensor.iter().zip(layers) {
+        assert_eq!(cells.len(), cells_y as usize);
+
+        for row in cells {
+            assert_eq!(row.len(), cells_x as usize);
+        }
+    }
+
+    assert_eq!(tensor[0][0][0], 1);
+    assert_eq!(tensor[0][0][2], -1);
+}
+
+#[test]
+pub fn tensor_round_trip() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    let tensor = vec![
+        vec![vec![0, 1, -1], vec![-1, -1, 2]],
+        vec![vec![-1, -1, -1], vec![3, 4, 5]],
+    ];
+
+    let level = project
+        .level_from_tensor(&tensor, Vec2 { x: 16, y: 16 })
+        .unwrap();
+
+    assert_eq!(level.width, 48.0);
+    assert_eq!(level.height, 32.0);
+    assert_eq!(level.to_tensor(&project), tensor);
+}
+
+#[test]
+pub fn tensor_mismatched_dimensions() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    let ragged = vec![vec![vec![0, 1], vec![2]], vec![vec![0, 1], vec![2, 3]]];
+    let too_few = vec![vec![vec![0, 1]]];
+
+    let cell_size = Vec2 { x: 16, y: 16 };
+
+    assert!(project.level_from_tensor(&ragged, cell_size).is_err());
+    assert!(project.level_from_tensor(&too_few, cell_size).is_err());
+}
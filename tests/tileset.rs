@@ -0,0 +1,244 @@
+use ogmo3::project::LayerTemplate;
+use ogmo3::{Error, Layer, Level, Project, Rect, Vec2};
+
+#[test]
+pub fn tileset_view() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    let view = project
+        .tileset_view("New Tileset", Vec2 { x: 64, y: 16 })
+        .unwrap();
+
+    assert_eq!(view.tile_count(), 4);
+    assert_eq!(view.tile_coords().count(), 4);
+
+    assert_eq!(
+        view.tile_rect(2),
+        Some(Rect {
+            x: 32,
+            y: 0,
+            width: 16,
+            height: 16
+        })
+    );
+    assert_eq!(view.tile_rect(4), None);
+    assert_eq!(view.tile_rect(-1), None);
+
+    assert_eq!(
+        view.tile_uvs(1),
+        Some((Vec2 { x: 0.25, y: 0.0 }, Vec2 { x: 0.5, y: 1.0 }))
+    );
+
+    assert!(project
+        .tileset_view("Missing", Vec2 { x: 64, y: 16 })
+        .is_none());
+}
+
+#[test]
+pub fn validate_tileset_against_texture() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut tileset = project.tilesets[0].clone();
+
+    assert!(tileset.validate_against(64, 16).is_ok());
+    assert!(matches!(
+        tileset.validate_against(70, 16),
+        Err(Error::Inconsistent(_))
+    ));
+
+    tileset.tile_separation_x = 2;
+    assert!(tileset.validate_against(72, 16).is_ok());
+    assert!(tileset.validate_against(64, 16).is_err());
+
+    tileset.tile_width = 0;
+    assert!(tileset.validate_against(64, 16).is_err());
+}
+
+#[test]
+pub fn tileset_by_label() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        if let Layer::Tile(layer) = layer {
+            let tileset = project.tileset_by_label(&layer.tileset).unwrap();
+            assert_eq!(tileset.tile_width, 16);
+        }
+    }
+
+    assert!(project.tileset_by_label("Missing").is_none());
+
+    project
+        .tileset_by_label_mut("New Tileset")
+        .unwrap()
+        .tile_separation_x = 2;
+
+    assert_eq!(project.tilesets[0].tile_separation_x, 2);
+}
+
+#[test]
+pub fn rename_tileset() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut levels = vec![
+        Level::from_file("./examples/sample_project/levels/uno.json").unwrap(),
+        project.new_level(64.0, 64.0),
+    ];
+
+    assert!(!project.rename_tileset("Missing", "Other", &mut levels));
+    assert!(project.rename_tileset("New Tileset", "Terrain", &mut levels));
+
+    assert!(project.tileset_by_label("New Tileset").is_none());
+    assert!(project.tileset_by_label("Terrain").is_some());
+
+    for template in &project.layers {
+        if let LayerTemplate::Tile(template) = template {
+            assert_eq!(template.default_tileset, "Terrain");
+        }
+    }
+
+    for level in &levels {
+        for layer in &level.layers {
+            match layer {
+                Layer::Tile(layer) => assert_eq!(layer.tileset, "Terrain"),
+                Layer::TileCoords(layer) => assert_eq!(layer.tileset, "Terrain"),
+                _ => {}
+            }
+        }
+
+        assert!(level.validate(&project).is_ok());
+    }
+}
+
+#[test]
+pub fn decode_tileset_image() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let image = project.tilesets[0].decode_image().unwrap();
+
+    assert_eq!(
+        image,
+        std::fs::read("./examples/sample_project/img/tiles.png").unwrap()
+    );
+
+    let mut tileset = project.tilesets[0].clone();
+
+    tileset.image = "aGVsbG8=".into();
+    assert_eq!(tileset.decode_image().unwrap(), b"hello");
+
+    tileset.image = "aGVsbG8".into();
+    assert_eq!(tileset.decode_image().unwrap(), b"hello");
+
+    tileset.image = "data:image/png;base64,aGV$bG8=".into();
+    assert!(matches!(tileset.decode_image(), Err(Error::Base64(_))));
+
+    tileset.image = "aGVsb".into();
+    assert!(matches!(tileset.decode_image(), Err(Error::Base64(_))));
+}
+
+#[test]
+pub fn decode_entity_texture_image() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut entity = project.entities[0].clone();
+
+    assert!(entity.decode_texture_image().unwrap().is_none());
+
+    entity.texture_image = Some("data:image/png;base64,b2dtbw==".into());
+    assert_eq!(entity.decode_texture_image().unwrap().unwrap(), b"ogmo");
+}
+
+#[test]
+pub fn tile_rect_with_separation() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut tileset = project.tilesets[0].clone();
+
+    tileset.tile_separation_x = 2;
+    tileset.tile_separation_y = 1;
+
+    assert_eq!(tileset.tile_count(72, 34), 8);
+
+    assert_eq!(
+        tileset.tile_rect(5, 72, 34),
+        Some(Rect {
+            x: 18,
+            y: 17,
+            width: 16,
+            height: 16
+        })
+    );
+    assert_eq!(tileset.tile_rect(8, 72, 34), None);
+
+    let coords: Vec<_> = tileset.tile_coords(72, 34).collect();
+    for (id, coord) in coords.iter().enumerate() {
+        let rect = tileset.tile_rect(id as i32, 72, 34).unwrap();
+        assert_eq!((rect.x, rect.y), (coord.x, coord.y));
+    }
+
+    tileset.tile_width = 0;
+    tileset.tile_separation_x = 0;
+    assert_eq!(tileset.tile_count(72, 34), 0);
+    assert_eq!(tileset.tile_coords(72, 34).count(), 0);
+}
+
+#[test]
+pub fn tile_coords_with_margin() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let tileset = &project.tilesets[0];
+
+    let coords: Vec<_> = tileset.tile_coords_with_margin(68, 20, 4, 4).collect();
+
+    assert_eq!(coords.len(), 4);
+    assert_eq!(coords[0], Vec2 { x: 4, y: 4 });
+    assert_eq!(coords[3], Vec2 { x: 52, y: 4 });
+
+    assert!(tileset
+        .tile_coords_with_margin(64, 16, 0, 0)
+        .eq(tileset.tile_coords(64, 16)));
+}
+
+#[test]
+pub fn tile_coords_reversed() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut tileset = project.tilesets[0].clone();
+    tileset.tile_separation_x = 2;
+    tileset.tile_separation_y = 1;
+
+    let coords = tileset.tile_coords(72, 34);
+    assert_eq!(coords.len(), 8);
+
+    let mut forwards: Vec<_> = coords.collect();
+    let backwards: Vec<_> = tileset.tile_coords(72, 34).rev().collect();
+
+    forwards.reverse();
+    assert_eq!(forwards, backwards);
+    assert_eq!(backwards[0], Vec2 { x: 54, y: 17 });
+
+    assert_eq!(tileset.tile_coords_with_margin(2, 2, 4, 4).len(), 0);
+}
+
+#[test]
+pub fn encode_embedded_images() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let png = std::fs::read("./examples/sample_project/img/tiles.png").unwrap();
+
+    let mut tileset = project.tilesets[0].clone();
+    tileset.image.clear();
+    tileset.set_image_bytes(&png);
+
+    assert_eq!(tileset.image, project.tilesets[0].image);
+    assert_eq!(tileset.decode_image().unwrap(), png);
+
+    for bytes in [&b""[..], b"h", b"he", b"hel", b"hello"] {
+        tileset.set_image_bytes(bytes);
+        assert_eq!(tileset.decode_image().unwrap(), bytes);
+    }
+
+    tileset.set_image_bytes(b"hello");
+    assert_eq!(tileset.image, "data:image/png;base64,aGVsbG8=");
+
+    let mut entity = project.entities[0].clone();
+    entity.set_texture_image_bytes(b"hi");
+
+    assert_eq!(
+        entity.texture_image.as_deref(),
+        Some("data:image/png;base64,aGk=")
+    );
+    assert_eq!(entity.decode_texture_image().unwrap().unwrap(), b"hi");
+}
@@ -0,0 +1,284 @@
+use std::io::{self, Read};
+
+use ogmo3::level::{GridLayerStorage, TileLayer, UnpackedLayer};
+use ogmo3::{Error, Layer, Level, Vec2};
+
+#[test]
+pub fn unpack_all_matches_serial_unpack() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    let unpacked = level.unpack_all();
+
+    assert_eq!(unpacked.layers.len(), level.layers.len());
+
+    for (layer, unpacked) in level.layers.iter().zip(&unpacked.layers) {
+        match (layer, unpacked) {
+            (Layer::Tile(layer), UnpackedLayer::Tile(tiles)) => {
+                assert_eq!(&layer.unpack().collect::<Vec<_>>(), tiles);
+            }
+
+            (Layer::TileCoords(layer), UnpackedLayer::TileCoords(tiles)) => {
+                assert_eq!(&layer.unpack().collect::<Vec<_>>(), tiles);
+            }
+
+            (Layer::Grid(layer), UnpackedLayer::Grid(cells)) => {
+                assert_eq!(&layer.unpack().collect::<Vec<_>>(), cells);
+            }
+
+            (Layer::Entity(layer), UnpackedLayer::Entity(entities)) => {
+                assert_eq!(layer.entities.len(), entities.len());
+            }
+
+            (Layer::Decal(layer), UnpackedLayer::Decal(decals)) => {
+                assert_eq!(layer.decals.len(), decals.len());
+            }
+
+            _ => panic!("unpacked layer '{}' has the wrong type", layer.name()),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+pub fn par_unpack_matches_serial_unpack() {
+    use rayon::iter::ParallelIterator;
+
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        if let Layer::Tile(layer) = layer {
+            let serial: Vec<_> = layer.unpack().collect();
+            let parallel: Vec<_> = layer.par_unpack().collect();
+
+            assert_eq!(serial, parallel);
+        }
+    }
+}
+
+#[test]
+pub fn unpack_reports_exact_length() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        let (len, count) = match layer {
+            Layer::Tile(layer) => (layer.unpack().len(), layer.unpack().count()),
+            Layer::TileCoords(layer) => (layer.unpack().len(), layer.unpack().count()),
+            Layer::Grid(layer) => (layer.unpack_with_offset().len(), layer.unpack().count()),
+            Layer::Entity(_) | Layer::Decal(_) | Layer::Unknown(_) => continue,
+        };
+
+        assert_eq!(
+            len,
+            count,
+            "layer '{}' reported the wrong length",
+            layer.name()
+        );
+    }
+
+    // The 2D grid layer has rows of different lengths, so the length can't just be
+    // calculated from the grid size.
+    let layer = match &level.layers[5] {
+        Layer::Grid(layer) => layer,
+        _ => panic!("expected a grid layer"),
+    };
+
+    let mut cells = layer.unpack();
+    let total = cells.len();
+
+    cells.next();
+    assert_eq!(cells.size_hint(), (total - 1, Some(total - 1)));
+}
+
+#[test]
+pub fn into_unpack_matches_unpack() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in level.layers.clone() {
+        match layer {
+            Layer::Tile(layer) => {
+                let borrowed: Vec<_> = layer.unpack().collect();
+                let owned = std::thread::spawn(move || layer.into_unpack().collect::<Vec<_>>());
+
+                assert_eq!(borrowed, owned.join().unwrap());
+            }
+
+            Layer::TileCoords(layer) => {
+                let borrowed: Vec<_> = layer.unpack().collect();
+                let owned: Vec<_> = layer.into_unpack().collect();
+
+                assert_eq!(borrowed, owned);
+            }
+
+            Layer::Grid(layer) => {
+                let borrowed: Vec<_> = layer
+                    .unpack()
+                    .map(|c| (c.value.to_owned(), c.grid_position, c.pixel_position))
+                    .collect();
+
+                let owned: Vec<_> = layer
+                    .into_unpack()
+                    .map(|c| (c.value, c.grid_position, c.pixel_position))
+                    .collect();
+
+                assert_eq!(borrowed, owned);
+            }
+
+            Layer::Entity(_) | Layer::Decal(_) | Layer::Unknown(_) => {}
+        }
+    }
+}
+
+#[test]
+pub fn grid_rows_match_unpack() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        if let Layer::Grid(layer) = layer {
+            let from_rows: Vec<_> = layer
+                .rows()
+                .enumerate()
+                .flat_map(|(y, row)| row.enumerate().map(move |(x, v)| (x as i32, y as i32, v)))
+                .collect();
+
+            let from_unpack: Vec<_> = layer
+                .unpack()
+                .map(|c| (c.grid_position.x, c.grid_position.y, c.value))
+                .collect();
+
+            assert_eq!(from_rows, from_unpack);
+        }
+    }
+
+    let grid_2d = level.layers[5].as_grid().unwrap();
+    let row_lengths: Vec<_> = grid_2d.rows().map(Iterator::count).collect();
+
+    match &grid_2d.data {
+        GridLayerStorage::Grid2D(data) => {
+            assert_eq!(row_lengths, data.iter().map(Vec::len).collect::<Vec<_>>());
+        }
+        GridLayerStorage::Grid(_) => panic!("expected 2D grid data"),
+    }
+}
+
+#[test]
+pub fn stream_data_matches_unpack() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in level.layers.iter().filter_map(Layer::as_tile) {
+        let layer_json = serde_json::to_vec(layer).unwrap();
+
+        let mut streamed = Vec::new();
+        let header =
+            TileLayer::stream_data(layer_json.as_slice(), |id, x, y| streamed.push((id, x, y)))
+                .unwrap();
+
+        let unpacked: Vec<_> = layer
+            .unpack()
+            .map(|t| (t.id.unwrap_or(-1), t.grid_position.x, t.grid_position.y))
+            .collect();
+
+        assert_eq!(streamed, unpacked);
+        assert_eq!(header.name, layer.name);
+        assert_eq!(header.grid_cells_x, layer.grid_cells_x);
+        assert_eq!(header.tileset, layer.tileset);
+        assert_eq!(header.unpack().len(), 0);
+    }
+}
+
+#[test]
+pub fn stream_data_requires_columns_before_data() {
+    let json = r#"{"name":"tiles","_eid":"1","data":[0,1],"gridCellWidth":8,"gridCellHeight":8,"gridCellsX":2,"gridCellsY":1,"tileset":"t"}"#;
+
+    assert!(matches!(
+        TileLayer::stream_data(json.as_bytes(), |_, _, _| {}),
+        Err(Error::Json(_))
+    ));
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "connection lost"))
+    }
+}
+
+#[test]
+pub fn stream_data_reader_errors() {
+    let reader = r#"{"name":"tiles","gridCellsX":2,"data":[0,"#
+        .as_bytes()
+        .chain(FailingReader);
+
+    match TileLayer::stream_data(reader, |_, _, _| {}) {
+        Err(Error::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+        other => panic!("expected an IO error, got {:?}", other),
+    }
+}
+
+#[test]
+pub fn unpack_with_world_offset() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    level.offset_x = 320.0;
+    level.offset_y = -240.0;
+
+    for layer in &mut level.layers {
+        if let Layer::Tile(layer) = layer {
+            layer.offset_x = 4.5;
+            layer.offset_y = 8.0;
+        }
+    }
+
+    assert_eq!(
+        level.world_offset(),
+        Vec2 {
+            x: 320.0,
+            y: -240.0
+        }
+    );
+
+    let layer = level.layers[0].as_tile().unwrap();
+
+    for (tile, world) in layer
+        .unpack()
+        .zip(layer.unpack_with_world_offset(level.world_offset()))
+    {
+        assert_eq!(world.grid_position, tile.grid_position);
+        assert_eq!(world.pixel_position.x, tile.pixel_position.x + 324);
+        assert_eq!(world.pixel_position.y, tile.pixel_position.y - 232);
+    }
+}
+
+#[test]
+pub fn iterate_layers_by_reference() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        match layer {
+            Layer::Tile(layer) => {
+                let mut count = 0;
+
+                for tile in layer {
+                    assert_eq!(
+                        layer.get(tile.grid_position.x, tile.grid_position.y),
+                        Some(tile)
+                    );
+                    count += 1;
+                }
+
+                assert_eq!(count, layer.unpack().len());
+            }
+
+            Layer::TileCoords(layer) => {
+                assert!(layer.into_iter().eq(layer.unpack()));
+            }
+
+            Layer::Grid(layer) => {
+                let iter = layer.into_iter();
+
+                assert_eq!(iter.len(), layer.unpack().count());
+                assert!(iter.eq(layer.unpack()));
+            }
+
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use ogmo3::level::{TileCoordsLayerStorage, TileLayerStorage};
+use ogmo3::project::{ColorValueTemplate, EnumValueTemplate, ValueTemplate};
+use ogmo3::{Error, Layer, Level, Project, ValidationError, Value, Vec2};
+use serde_json::json;
+
+fn load_project() -> Project {
+    Project::from_file("./examples/sample_project/test.ogmo").unwrap()
+}
+
+#[test]
+pub fn validate_sample_project() {
+    assert!(load_project().validate().is_ok());
+}
+
+#[test]
+pub fn validate_value_defaults() {
+    let mut project = load_project();
+
+    project
+        .level_values
+        .push(ValueTemplate::Color(ColorValueTemplate {
+            name: "tint".into(),
+            defaults: "#ff00zz".into(),
+            include_alpha: false,
+            extra: Default::default(),
+        }));
+
+    project.entities[0]
+        .values
+        .push(ValueTemplate::Enum(EnumValueTemplate {
+            name: "mood".into(),
+            defaults: 2,
+            choices: vec!["happy".into(), "sad".into()],
+            extra: Default::default(),
+        }));
+
+    let errors = project.validate().unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![
+            ValidationError::InvalidColorDefault {
+                template: "tint".into(),
+                value: "#ff00zz".into(),
+            },
+            ValidationError::InvalidEnumDefault {
+                template: "mood".into(),
+                index: 2,
+                choices: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+pub fn validate_sample_level() {
+    let project = load_project();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    assert!(level.validate(&project).is_ok());
+}
+
+#[test]
+pub fn validate_level_against_project() {
+    let mut project = load_project();
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    project.layers.retain(|l| l.name() != "grid_1D");
+    project.tilesets[0].label = "Renamed Tileset".into();
+    project.entities.retain(|e| e.name != "Grom");
+
+    let errors = level.validate(&project).unwrap_err();
+
+    assert!(errors.contains(&ValidationError::UnknownLayerTemplate {
+        layer: "grid_1D".into(),
+        export_id: level
+            .layers
+            .iter()
+            .find(|l| l.name() == "grid_1D")
+            .unwrap()
+            .export_id()
+            .into(),
+    }));
+
+    assert!(errors.contains(&ValidationError::UnknownTileset {
+        layer: "tile_1D".into(),
+        tileset: "New Tileset".into(),
+    }));
+
+    assert!(errors.contains(&ValidationError::UnknownEntityTemplate {
+        layer: "entity_layer".into(),
+        entity: "Grom".into(),
+    }));
+
+    // One for the grid layer, one for each of the four tile layers, and one for the entity.
+    assert_eq!(errors.len(), 6);
+}
+
+#[test]
+pub fn validate_values_against_templates() {
+    let template = |value| serde_json::from_value::<ValueTemplate>(value).unwrap();
+
+    let integer = template(
+        json!({"name": "hp", "definition": "Integer", "defaults": 1, "bounded": true, "min": 0, "max": 10}),
+    );
+    let float = template(
+        json!({"name": "speed", "definition": "Float", "defaults": 1, "bounded": true, "min": 0.5, "max": 2}),
+    );
+    let choice = template(
+        json!({"name": "mood", "definition": "Enum", "defaults": 0, "choices": ["happy", "sad"]}),
+    );
+    let string = template(
+        json!({"name": "title", "definition": "String", "defaults": "", "maxLength": 3, "trimWhitespace": false}),
+    );
+
+    assert!(integer.validate(&Value::Integer(10)).is_ok());
+    assert!(float.validate(&Value::Number(1.5)).is_ok());
+    assert!(choice.validate(&Value::String("sad".into())).is_ok());
+    assert!(string.validate(&Value::String("abc".into())).is_ok());
+
+    assert_eq!(
+        integer.validate(&Value::Integer(11)),
+        Err(ValidationError::ValueOutOfBounds {
+            template: "hp".into(),
+            value: "11".into(),
+            min: 0.0,
+            max: 10.0,
+        })
+    );
+
+    assert_eq!(
+        float.validate(&Value::Number(0.25)),
+        Err(ValidationError::ValueOutOfBounds {
+            template: "speed".into(),
+            value: "0.25".into(),
+            min: 0.5,
+            max: 2.0,
+        })
+    );
+
+    assert_eq!(
+        choice.validate(&Value::Integer(2)),
+        Err(ValidationError::InvalidEnumValue {
+            template: "mood".into(),
+            value: "2".into(),
+            choices: 2,
+        })
+    );
+
+    assert_eq!(
+        string.validate(&Value::String("abcd".into())),
+        Err(ValidationError::StringTooLong {
+            template: "title".into(),
+            length: 4,
+            max_length: 3,
+        })
+    );
+
+    assert_eq!(
+        integer.validate(&Value::String("ten".into())),
+        Err(ValidationError::MismatchedValue {
+            template: "hp".into(),
+            value: "\"ten\"".into(),
+        })
+    );
+}
+
+#[test]
+pub fn validate_level_values() {
+    let project = load_project();
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    level
+        .values
+        .insert("poop".into(), Value::String("not a color".into()));
+
+    if let Layer::Entity(layer) = &mut level.layers[6] {
+        layer.entities[0].set_value("Bool value!", Value::Integer(1));
+    }
+
+    assert_eq!(
+        level.validate(&project).unwrap_err(),
+        vec![
+            ValidationError::MismatchedValue {
+                template: "Bool value!".into(),
+                value: "1".into(),
+            },
+            ValidationError::MismatchedValue {
+                template: "poop".into(),
+                value: "\"not a color\"".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+pub fn validate_malformed_tile_coords() {
+    let project = load_project();
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    match &mut level.layers[2].as_tile_coords_mut().unwrap().data {
+        TileCoordsLayerStorage::DataCoords(data) => data[0] = vec![5],
+        TileCoordsLayerStorage::DataCoords2D(_) => panic!("expected 1D tile co-ords"),
+    }
+
+    match &mut level.layers[3].as_tile_coords_mut().unwrap().data {
+        TileCoordsLayerStorage::DataCoords2D(data) => data[0][1] = vec![1, 2, 3],
+        TileCoordsLayerStorage::DataCoords(_) => panic!("expected 2D tile co-ords"),
+    }
+
+    let errors = level.validate(&project).unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![
+            ValidationError::MalformedTileCoords {
+                layer: "tileCoords_1D".into(),
+                grid_position: Vec2 { x: 0, y: 0 },
+                coords: vec![5],
+            },
+            ValidationError::MalformedTileCoords {
+                layer: "tileCoords_2D".into(),
+                grid_position: Vec2 { x: 1, y: 0 },
+                coords: vec![1, 2, 3],
+            },
+        ]
+    );
+
+    let layer = level.layers[2].as_tile_coords().unwrap();
+
+    assert_eq!(layer.get(0, 0).unwrap().grid_coords, None);
+    assert_eq!(layer.unpack().count(), layer.unpack().len());
+}
+
+#[test]
+pub fn check_versions() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    assert!(project.check_version().is_ok());
+    assert!(level.check_version().is_ok());
+
+    project.ogmo_version = "4.0.0".into();
+    level.ogmo_version = "garbage".into();
+
+    match project.check_version() {
+        Err(Error::UnsupportedVersion { found, .. }) => assert_eq!(found, "4.0.0"),
+        other => panic!("expected unsupported version, got {:?}", other),
+    }
+
+    assert!(matches!(
+        level.check_version(),
+        Err(Error::UnsupportedVersion { .. })
+    ));
+}
+
+#[test]
+pub fn validate_tile_dimensions() {
+    let project = load_project();
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for layer in &level.layers {
+        if let Layer::Tile(layer) = layer {
+            assert!(layer.check_dimensions().is_ok());
+        }
+    }
+
+    if let Some(Layer::Tile(layer)) = level.layers.get_mut(0) {
+        layer.grid_cells_y += 1;
+        assert!(matches!(
+            layer.check_dimensions(),
+            Err(Error::Inconsistent(_))
+        ));
+    }
+
+    if let Some(Layer::Tile(layer)) = level.layers.get_mut(1) {
+        if let TileLayerStorage::Data2D(rows) = &mut layer.data {
+            rows[3].pop();
+        }
+
+        assert!(matches!(
+            layer.check_dimensions(),
+            Err(Error::Inconsistent(_))
+        ));
+    }
+
+    let errors = level.validate(&project).unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![
+            ValidationError::MismatchedDimensions {
+                layer: "tile_1D".into(),
+                message: "expected 320 tiles, found 300".into(),
+            },
+            ValidationError::MismatchedDimensions {
+                layer: "tile_2D".into(),
+                message: "expected 20 tiles in row 3, found 19".into(),
+            },
+        ]
+    );
+}
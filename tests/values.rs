@@ -0,0 +1,525 @@
+use ogmo3::level::{Layer, TypedValue};
+use ogmo3::project::{EnumValueTemplate, ValueTemplate};
+use ogmo3::{Color, Error, Level, Project, Value, Vec2};
+use serde_json::json;
+
+fn load_entity(name: &str) -> ogmo3::level::Entity {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    level
+        .layers
+        .into_iter()
+        .find_map(|layer| match layer {
+            Layer::Entity(layer) => layer.entities.into_iter().find(|e| e.name == name),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+pub fn entity_set_value() {
+    let mut entity = load_entity("Grom");
+    assert!(entity.values.is_none());
+
+    assert!(entity.set_value("health", Value::Number(10.0)).is_none());
+    assert!(matches!(
+        entity.set_value("health", Value::Number(5.0)),
+        Some(Value::Number(v)) if v == 10.0
+    ));
+
+    let values = entity.values.as_ref().unwrap();
+    assert_eq!(values.len(), 1);
+    assert!(matches!(values["health"], Value::Number(v) if v == 5.0));
+}
+
+#[test]
+pub fn entity_remove_value() {
+    let mut entity = load_entity("Dude");
+
+    assert!(entity.remove_value("missing").is_none());
+    assert!(entity.values.is_some());
+
+    assert!(matches!(
+        entity.remove_value("Bool value!"),
+        Some(Value::Boolean(false))
+    ));
+    assert!(entity.values.is_none());
+    assert!(!serde_json::to_string(&entity).unwrap().contains("values"));
+}
+
+#[test]
+pub fn decal_values() {
+    let level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    let mut decal = level
+        .layers
+        .into_iter()
+        .find_map(|layer| match layer {
+            Layer::Decal(layer) => layer.decals.into_iter().next(),
+            _ => None,
+        })
+        .unwrap();
+
+    decal.set_value("label", Value::String("sign".into()));
+    assert_eq!(decal.values.len(), 2);
+
+    assert!(matches!(
+        decal.remove_value("Decal_value"),
+        Some(Value::Boolean(false))
+    ));
+    assert!(decal.remove_value("Decal_value").is_none());
+    assert_eq!(decal.values.len(), 1);
+}
+
+#[test]
+pub fn integer_values() {
+    let mut entity = load_entity("Grom");
+
+    entity.set_value("health", Value::Integer(10));
+    entity.set_value("speed", Value::Number(2.5));
+
+    let json = serde_json::to_string(&entity).unwrap();
+    assert!(json.contains(r#""health":10"#));
+    assert!(json.contains(r#""speed":2.5"#));
+
+    let entity: ogmo3::level::Entity = serde_json::from_str(&json).unwrap();
+    let values = entity.values.unwrap();
+
+    assert!(matches!(values["health"], Value::Integer(10)));
+    assert!(matches!(values["speed"], Value::Number(v) if v == 2.5));
+}
+
+#[test]
+pub fn entity_template_default_values() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let mut template = project
+        .entities
+        .iter()
+        .find(|e| e.name == "Dude")
+        .unwrap()
+        .clone();
+
+    template.values.extend(
+        vec![
+            json!({"name": "health", "definition": "Integer", "defaults": 10, "bounded": false, "min": 0, "max": 100}),
+            json!({"name": "speed", "definition": "Float", "defaults": 2.5, "bounded": false, "min": 0, "max": 10}),
+            json!({"name": "label", "definition": "String", "defaults": "hello", "maxLength": 0, "trimWhitespace": true}),
+            json!({"name": "kind", "definition": "Enum", "defaults": 1, "choices": ["a", "b"]}),
+        ]
+        .into_iter()
+        .map(|v| serde_json::from_value::<ValueTemplate>(v).unwrap()),
+    );
+
+    let values = template.default_values();
+
+    assert_eq!(values.len(), 5);
+    assert!(matches!(values["Bool value!"], Value::Boolean(false)));
+    assert!(matches!(values["health"], Value::Integer(10)));
+    assert!(matches!(values["speed"], Value::Number(v) if v == 2.5));
+    assert!(matches!(&values["label"], Value::String(s) if s == "hello"));
+    assert!(matches!(&values["kind"], Value::String(s) if s == "b"));
+
+    let grom = project.entities.iter().find(|e| e.name == "Grom").unwrap();
+    assert!(grom.default_values().is_empty());
+}
+
+#[test]
+pub fn instantiate_entity_template() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    let dude = project
+        .entity_template_by_name("Dude")
+        .unwrap()
+        .instantiate(7, Vec2 { x: 32.0, y: 48.0 });
+
+    assert_eq!(dude.name, "Dude");
+    assert_eq!(dude.id, 7);
+    assert_eq!(dude.export_id, "09166515");
+    assert_eq!((dude.x, dude.y), (32.0, 48.0));
+    assert_eq!((dude.width, dude.height), (Some(16.0), Some(16.0)));
+    assert_eq!(dude.rotation, Some(0.0));
+    assert_eq!((dude.flipped_x, dude.flipped_y), (Some(false), Some(false)));
+    assert_eq!(dude.nodes, Some(vec![]));
+    assert!(matches!(
+        dude.values.as_ref().unwrap()["Bool value!"],
+        Value::Boolean(false)
+    ));
+
+    let grom = project
+        .entity_template_by_name("Grom")
+        .unwrap()
+        .instantiate(8, Vec2 { x: 0.0, y: 0.0 });
+
+    assert_eq!((grom.width, grom.height), (None, None));
+    assert_eq!(grom.rotation, None);
+    assert_eq!((grom.flipped_x, grom.flipped_y), (None, None));
+    assert_eq!(grom.nodes, None);
+    assert!(grom.values.is_none());
+
+    assert_eq!(
+        serde_json::to_string(&grom).unwrap(),
+        r#"{"name":"Grom","id":8,"_eid":"15255645","x":0.0,"y":0.0}"#
+    );
+}
+
+#[test]
+pub fn value_template_default_value() {
+    let cases = vec![
+        (
+            json!({"name": "a", "definition": "Boolean", "defaults": true}),
+            Value::Boolean(true),
+        ),
+        (
+            json!({"name": "b", "definition": "Color", "defaults": "#ff0000ff", "includeAlpha": true}),
+            Value::String("#ff0000ff".into()),
+        ),
+        (
+            json!({"name": "c", "definition": "Enum", "defaults": 2, "choices": ["x", "y", "z"]}),
+            Value::String("z".into()),
+        ),
+        (
+            json!({"name": "c2", "definition": "Enum", "defaults": 3, "choices": ["x", "y", "z"]}),
+            Value::Integer(3),
+        ),
+        (
+            json!({"name": "d", "definition": "Integer", "defaults": -4, "bounded": false, "min": 0, "max": 0}),
+            Value::Integer(-4),
+        ),
+        (
+            json!({"name": "e", "definition": "Float", "defaults": 0.5, "bounded": false, "min": 0, "max": 0}),
+            Value::Number(0.5),
+        ),
+        (
+            json!({"name": "f", "definition": "String", "defaults": "hi", "maxLength": 0, "trimWhitespace": false}),
+            Value::String("hi".into()),
+        ),
+        (
+            json!({"name": "g", "definition": "Text", "defaults": "long text"}),
+            Value::String("long text".into()),
+        ),
+    ];
+
+    for (template, expected) in cases {
+        let template: ValueTemplate = serde_json::from_value(template).unwrap();
+
+        assert_eq!(
+            template.default_value(),
+            expected,
+            "wrong default for '{}'",
+            template.name()
+        );
+
+        // Valid defaults keep the same form when resolved and converted back.
+        if let Ok(typed) = template.resolve(&expected) {
+            assert_eq!(typed.to_value(), expected);
+        }
+    }
+}
+
+#[test]
+pub fn value_template_resolve() {
+    let template = |value| serde_json::from_value::<ValueTemplate>(value).unwrap();
+
+    let boolean = template(json!({"name": "a", "definition": "Boolean", "defaults": true}));
+    let color = template(
+        json!({"name": "b", "definition": "Color", "defaults": "#ff0000ff", "includeAlpha": true}),
+    );
+    let choice = template(
+        json!({"name": "c", "definition": "Enum", "defaults": 2, "choices": ["x", "y", "z"]}),
+    );
+    let integer = template(
+        json!({"name": "d", "definition": "Integer", "defaults": -4, "bounded": false, "min": 0, "max": 0}),
+    );
+    let float = template(
+        json!({"name": "e", "definition": "Float", "defaults": 0.5, "bounded": false, "min": 0, "max": 0}),
+    );
+    let text = template(json!({"name": "g", "definition": "Text", "defaults": "long text"}));
+
+    assert_eq!(
+        boolean.resolve(&Value::Boolean(true)).unwrap(),
+        TypedValue::Boolean(true)
+    );
+
+    assert_eq!(
+        color.resolve(&Value::String("#ff0000".into())).unwrap(),
+        TypedValue::Color(Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255
+        })
+    );
+
+    assert_eq!(
+        choice.resolve(&Value::String("y".into())).unwrap(),
+        TypedValue::Enum("y".into())
+    );
+    assert_eq!(
+        choice.resolve(&Value::Integer(2)).unwrap(),
+        TypedValue::Enum("z".into())
+    );
+
+    assert_eq!(
+        integer.resolve(&Value::Number(3.0)).unwrap(),
+        TypedValue::Integer(3)
+    );
+    assert_eq!(
+        float.resolve(&Value::Integer(3)).unwrap(),
+        TypedValue::Float(3.0)
+    );
+    assert_eq!(
+        text.resolve(&Value::String("hi".into())).unwrap(),
+        TypedValue::Text("hi".into())
+    );
+
+    assert!(matches!(
+        boolean.resolve(&Value::Integer(1)),
+        Err(Error::Inconsistent(_))
+    ));
+    assert!(matches!(
+        choice.resolve(&Value::Integer(3)),
+        Err(Error::Inconsistent(_))
+    ));
+    assert!(matches!(
+        integer.resolve(&Value::Number(3.5)),
+        Err(Error::Inconsistent(_))
+    ));
+    assert!(matches!(
+        color.resolve(&Value::String("red".into())),
+        Err(Error::InvalidColor(_))
+    ));
+}
+
+#[test]
+pub fn typed_level() {
+    let project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+    let json = std::fs::read_to_string("./examples/sample_project/levels/uno.json").unwrap();
+
+    let level = Level::from_json_with_project(&json, &project).unwrap();
+
+    assert_eq!(
+        level.values["poop"],
+        TypedValue::Color(Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255
+        })
+    );
+
+    let layer = level
+        .level
+        .layers
+        .iter()
+        .position(|l| l.as_entity().is_some())
+        .unwrap();
+
+    assert_eq!(
+        level.entity_values[&(layer, 0)]["Bool value!"],
+        TypedValue::Boolean(false)
+    );
+    assert!(level.entity_values[&(layer, 1)].is_empty());
+
+    assert_eq!(
+        level.to_json().unwrap(),
+        Level::from_json(&json).unwrap().to_json().unwrap()
+    );
+
+    let mut edited = level.clone();
+    edited.values.insert(
+        "poop".into(),
+        TypedValue::Color(Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        }),
+    );
+    edited
+        .entity_values
+        .get_mut(&(layer, 0))
+        .unwrap()
+        .insert("Bool value!".into(), TypedValue::Boolean(true));
+
+    let output = Level::from_json(&edited.to_json().unwrap()).unwrap();
+    assert_eq!(output.values["poop"], Value::String("#ff0000ff".into()));
+    assert_eq!(
+        output.entities().next().unwrap().values.as_ref().unwrap()["Bool value!"],
+        Value::Boolean(true)
+    );
+
+    // Entities with the same ID are still resolved and edited separately.
+    let mut duplicated = Level::from_json(&json).unwrap();
+    let entities = &mut duplicated.layers[layer].as_entity_mut().unwrap().entities;
+    entities.push(entities[0].clone());
+
+    let mut typed = duplicated.into_typed(&project).unwrap();
+    typed
+        .entity_values
+        .get_mut(&(layer, 4))
+        .unwrap()
+        .insert("Bool value!".into(), TypedValue::Boolean(true));
+
+    let output = typed.to_level();
+    let entities = &output.layers[layer].as_entity().unwrap().entities;
+    assert_eq!(entities[0].id, entities[4].id);
+    assert_eq!(
+        entities[0].values.as_ref().unwrap()["Bool value!"],
+        Value::Boolean(false)
+    );
+    assert_eq!(
+        entities[4].values.as_ref().unwrap()["Bool value!"],
+        Value::Boolean(true)
+    );
+
+    let mut removed = level.clone();
+    removed.values.retain(|k, _| k != "poop");
+    assert!(removed.to_level().values.is_empty());
+
+    let mut untyped = Level::from_json(&json).unwrap();
+    untyped.values.retain(|k, _| k != "poop");
+
+    let typed = untyped.clone().into_typed(&project).unwrap();
+    assert_eq!(
+        typed.values["poop"],
+        TypedValue::Color(Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255
+        })
+    );
+
+    untyped
+        .values
+        .insert("unknown".into(), Value::Boolean(true));
+    assert!(matches!(
+        untyped.into_typed(&project),
+        Err(Error::Inconsistent(_))
+    ));
+}
+
+#[test]
+pub fn value_equality() {
+    assert_eq!(Value::Number(f32::NAN), Value::Number(f32::NAN));
+    assert_eq!(Value::Number(1.5), Value::Number(1.5));
+    assert_ne!(Value::Number(0.0), Value::Number(-0.0));
+    assert_ne!(Value::Integer(16), Value::Number(16.0));
+    assert_ne!(Value::Boolean(true), Value::String("true".into()));
+}
+
+#[test]
+pub fn value_display() {
+    assert_eq!(Value::Boolean(true).to_string(), "true");
+    assert_eq!(Value::String("#ff0000ff".into()).to_string(), "#ff0000ff");
+    assert_eq!(Value::Integer(-3).to_string(), "-3");
+    assert_eq!(Value::Number(16.0).to_string(), "16");
+    assert_eq!(Value::Number(1.5).to_string(), "1.5");
+}
+
+#[test]
+pub fn default_level_values() {
+    let mut project = Project::from_file("./examples/sample_project/test.ogmo").unwrap();
+
+    project
+        .level_values
+        .push(ValueTemplate::Enum(EnumValueTemplate {
+            name: "mood".into(),
+            defaults: 1,
+            choices: vec!["happy".into(), "sad".into()],
+            extra: Default::default(),
+        }));
+
+    let defaults = project.default_level_values();
+
+    assert_eq!(defaults.len(), 2);
+    assert_eq!(defaults["poop"], Value::String("#000000ff".into()));
+    assert_eq!(defaults["mood"], Value::String("sad".into()));
+
+    assert_eq!(project.new_level(64.0, 64.0).values, defaults);
+
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+    level.values = project.default_level_values();
+
+    assert!(level.validate(&project).is_ok());
+}
+
+#[test]
+pub fn try_from_values() -> Result<(), Error> {
+    use std::convert::{TryFrom, TryInto};
+
+    let hp: i32 = (&Value::Integer(10)).try_into()?;
+    assert_eq!(hp, 10);
+
+    let truncated: i32 = (&Value::Number(-1.9)).try_into()?;
+    assert_eq!(truncated, -1);
+
+    let speed: f32 = (&Value::Number(1.5)).try_into()?;
+    assert_eq!(speed, 1.5);
+
+    let whole: f32 = (&Value::Integer(3)).try_into()?;
+    assert_eq!(whole, 3.0);
+
+    let flag: bool = (&Value::Boolean(true)).try_into()?;
+    assert!(flag);
+
+    let name: String = (&Value::String("Dude".into())).try_into()?;
+    assert_eq!(name, "Dude");
+
+    assert!(matches!(
+        i32::try_from(&Value::Integer(i64::MAX)),
+        Err(Error::Inconsistent(_))
+    ));
+    assert!(matches!(
+        bool::try_from(&Value::String("true".into())),
+        Err(Error::Inconsistent(_))
+    ));
+    assert!(matches!(
+        f32::try_from(&Value::Boolean(false)),
+        Err(Error::Inconsistent(_))
+    ));
+    assert!(matches!(
+        String::try_from(&Value::Integer(1)),
+        Err(Error::Inconsistent(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn values_sorted() {
+    let mut level = Level::from_file("./examples/sample_project/levels/uno.json").unwrap();
+
+    for key in &["zeta", "alpha", "mu"] {
+        level.values.insert(key.to_string(), Value::Boolean(true));
+    }
+
+    let keys: Vec<&str> = level.values_sorted().map(|(k, _)| k).collect();
+    assert_eq!(keys, ["alpha", "mu", "poop", "zeta"]);
+
+    let mut entity = load_entity("Grom");
+    assert_eq!(entity.values_sorted().count(), 0);
+
+    entity.set_value("speed", Value::Integer(2));
+    entity.set_value("health", Value::Integer(10));
+
+    let values: Vec<_> = entity.values_sorted().collect();
+    assert!(matches!(
+        values.as_slice(),
+        [("health", Value::Integer(10)), ("speed", Value::Integer(2))]
+    ));
+
+    let mut decal = level
+        .layers
+        .into_iter()
+        .find_map(|layer| match layer {
+            Layer::Decal(layer) => layer.decals.into_iter().next(),
+            _ => None,
+        })
+        .unwrap();
+
+    decal.set_value("label", Value::String("sign".into()));
+
+    let keys: Vec<&str> = decal.values_sorted().map(|(k, _)| k).collect();
+    assert_eq!(keys, ["Decal_value", "label"]);
+}
@@ -0,0 +1,62 @@
+use ogmo3::Vec2;
+
+#[test]
+pub fn tuple_conversions() {
+    let v: Vec2<i32> = (1, 2).into();
+    assert_eq!(v, Vec2 { x: 1, y: 2 });
+
+    let t: (i32, i32) = v.into();
+    assert_eq!(t, (1, 2));
+}
+
+#[test]
+pub fn map_components() {
+    let v = Vec2 { x: 3, y: -4 };
+    assert_eq!(v.map(|c| c as f32 * 0.5), Vec2 { x: 1.5, y: -2.0 });
+}
+
+#[test]
+pub fn hash_map_key() {
+    let mut cache = std::collections::HashMap::new();
+    cache.insert(Vec2 { x: 1, y: 2 }, "tile");
+
+    assert_eq!(cache.get(&Vec2 { x: 1, y: 2 }), Some(&"tile"));
+    assert_eq!(cache.get(&Vec2 { x: 2, y: 1 }), None);
+}
+
+#[test]
+pub fn array_conversions() {
+    let v = Vec2 { x: 1.5f32, y: 2.0 };
+    assert_eq!(v.to_array(), [1.5, 2.0]);
+    assert_eq!(Vec2::from([1.5f32, 2.0]), v);
+
+    let v = Vec2 { x: 3, y: 4 };
+    let a: [i32; 2] = v.into();
+    assert_eq!(a, [3, 4]);
+    assert_eq!(Vec2::from(a), v);
+}
+
+#[test]
+pub fn constructors() {
+    assert_eq!(Vec2::<i32>::zero(), Vec2 { x: 0, y: 0 });
+    assert_eq!(Vec2::<f32>::zero(), Vec2 { x: 0.0, y: 0.0 });
+
+    assert_eq!(Vec2::splat(3), Vec2 { x: 3, y: 3 });
+    assert_eq!(Vec2::splat(-1.5f32), Vec2 { x: -1.5, y: -1.5 });
+}
+
+#[test]
+pub fn dot_and_length() {
+    let a = Vec2 { x: 3, y: 4 };
+    let b = Vec2 { x: -2, y: 1 };
+
+    assert_eq!(a.dot(b), -2);
+    assert_eq!(a.length_squared(), 25);
+
+    let a = Vec2 { x: 1.5f32, y: -2.0 };
+    let b = Vec2 { x: 2.0f32, y: 0.5 };
+
+    assert_eq!(a.dot(b), 2.0);
+    assert_eq!(a.length_squared(), 6.25);
+    assert_eq!(Vec2::<f32>::zero().length_squared(), 0.0);
+}